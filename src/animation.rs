@@ -0,0 +1,177 @@
+use std::{collections::HashMap, f32::consts::PI, hash::Hash};
+
+use macroquad::color::Color;
+
+/// How a [`Timeline`] moves from one keyframe's value to the next
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly, following a quarter of a sine wave
+    SineIn,
+    /// Ends slowly, following a quarter of a sine wave
+    SineOut,
+    /// Starts and ends slowly, following half of a sine wave
+    SineInOut,
+}
+
+impl Easing {
+    /// Maps the fraction of the way through a transition to the fraction of the way between the
+    /// values
+    #[must_use]
+    pub fn apply(self, fraction: f32) -> f32 {
+        let fraction = fraction.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => fraction,
+            Easing::SineIn => 1.0 - (fraction * PI / 2.0).cos(),
+            Easing::SineOut => (fraction * PI / 2.0).sin(),
+            Easing::SineInOut => (1.0 - (fraction * PI).cos()) / 2.0,
+        }
+    }
+}
+
+/// A value which can be animated by a [`Timeline`]
+pub trait Lerp: Copy {
+    /// The value a fraction of the way from `self` to `other`
+    #[must_use]
+    fn lerp(self, other: Self, fraction: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, fraction: f32) -> Self {
+        self + (other - self) * fraction
+    }
+}
+
+impl Lerp for [f32; 2] {
+    fn lerp(self, other: Self, fraction: f32) -> Self {
+        [0, 1].map(|i| self[i].lerp(other[i], fraction))
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, other: Self, fraction: f32) -> Self {
+        Color {
+            r: self.r.lerp(other.r, fraction),
+            g: self.g.lerp(other.g, fraction),
+            b: self.b.lerp(other.b, fraction),
+            a: self.a.lerp(other.a, fraction),
+        }
+    }
+}
+
+/// A value which changes over time, easing between keyframes. Timelines are built up from their
+/// starting value with [`Timeline::then`], and hold their last value once they end unless they
+/// loop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline<T> {
+    start: T,
+    keyframes: Vec<Keyframe<T>>,
+    looping: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Keyframe<T> {
+    /// The time in seconds from the start of the timeline that the value is reached at
+    time: f32,
+    value: T,
+    /// How the value eases from the previous keyframe to this one
+    easing: Easing,
+}
+
+impl<T: Lerp> Timeline<T> {
+    #[must_use]
+    pub fn new(start: T) -> Self {
+        Self {
+            start,
+            keyframes: Vec::new(),
+            looping: false,
+        }
+    }
+
+    /// Adds a keyframe `duration` seconds after the last one, which the value eases to
+    #[must_use]
+    pub fn then(mut self, duration: f32, value: T, easing: Easing) -> Self {
+        self.keyframes.push(Keyframe {
+            time: self.duration() + duration,
+            value,
+            easing,
+        });
+
+        self
+    }
+
+    /// Makes the timeline start again from the beginning once it ends
+    #[must_use]
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// The time in seconds from the start of the timeline to its last keyframe
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// The value the given number of seconds after the start of the timeline
+    #[must_use]
+    pub fn sample(&self, time: f32) -> T {
+        let duration = self.duration();
+
+        let time = if self.looping && duration > 0.0 {
+            time.rem_euclid(duration)
+        } else {
+            time
+        };
+
+        let mut previous = (0.0, self.start);
+
+        for keyframe in &self.keyframes {
+            if time < keyframe.time {
+                let (previous_time, previous_value) = previous;
+                let fraction = (time - previous_time) / (keyframe.time - previous_time);
+
+                return previous_value.lerp(keyframe.value, keyframe.easing.apply(fraction));
+            }
+
+            previous = (keyframe.time, keyframe.value);
+        }
+
+        previous.1
+    }
+}
+
+/// How long each of a set of things has been animating for, such as the gems in the levels. This
+/// is kept by whatever draws them rather than in the game state, so drawing never changes what the
+/// game hashes, saves or replays.
+#[derive(Clone, Debug)]
+pub struct AnimationClocks<K> {
+    times: HashMap<K, f32>,
+    period: f32,
+}
+
+impl<K: Eq + Hash> AnimationClocks<K> {
+    /// Creates clocks which wrap back to zero after `period` seconds, which should be a multiple
+    /// of the durations of the looping timelines they're used with so the wrap can't be seen
+    #[must_use]
+    pub fn new(period: f32) -> Self {
+        Self {
+            times: HashMap::new(),
+            period,
+        }
+    }
+
+    /// Moves the clock for `key` forward, starting it if it wasn't running
+    pub fn advance(&mut self, key: K, seconds: f32) {
+        let time = self.times.entry(key).or_default();
+        *time = (*time + seconds).rem_euclid(self.period);
+    }
+
+    /// The time on the clock for `key`, which is zero if it hasn't started
+    #[must_use]
+    pub fn time(&self, key: &K) -> f32 {
+        self.times.get(key).copied().unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,60 @@
+//! Checks levels files without opening a window, so level packs can be checked in scripts. Prints
+//! every problem found and exits with a failure if there were any.
+//!
+//! Usage: `validate_levels <file>...`
+
+use std::{env, fs, process::ExitCode};
+
+use inverse::level::Levels;
+
+fn main() -> ExitCode {
+    let paths = env::args().skip(1).collect::<Vec<_>>();
+
+    if paths.is_empty() {
+        eprintln!("usage: validate_levels <file>...");
+        return ExitCode::FAILURE;
+    }
+
+    let mut valid = true;
+
+    for path in &paths {
+        valid &= validate(path);
+    }
+
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Prints the problems with one levels file, returning `true` if there weren't any
+fn validate(path: &str) -> bool {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return false;
+        }
+    };
+
+    let levels = match Levels::parse_located(&text) {
+        Ok(levels) => levels,
+        Err(error) => {
+            eprintln!("{path}: {error}");
+            return false;
+        }
+    };
+
+    let problems = levels.problems();
+
+    for problem in &problems {
+        eprintln!("{path}: {problem}");
+    }
+
+    if problems.is_empty() {
+        println!("{path}: {} levels, all valid", levels.num_levels);
+    }
+
+    problems.is_empty()
+}
@@ -0,0 +1,76 @@
+use std::{fmt::Display, str::FromStr};
+
+/// The changes in each version of the game, read from a file with a `[version]` line before each
+/// version's changes, one per line. Versions go from newest to oldest, and lines starting with `#`
+/// are comments.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changelog {
+    pub releases: Vec<Release>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    pub changes: Vec<String>,
+}
+
+impl Changelog {
+    /// The releases newer than the given version, newest first. Every release is newer than a
+    /// version which isn't in the changelog.
+    #[must_use]
+    pub fn since(&self, version: &str) -> &[Release] {
+        let end = self
+            .releases
+            .iter()
+            .position(|release| release.version == version)
+            .unwrap_or(self.releases.len());
+
+        &self.releases[..end]
+    }
+}
+
+impl FromStr for Changelog {
+    type Err = ParseChangelogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut releases = Vec::<Release>::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(version) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                releases.push(Release {
+                    version: version.trim().to_owned(),
+                    changes: Vec::new(),
+                });
+            } else {
+                releases
+                    .last_mut()
+                    .ok_or(ParseChangelogError(i))?
+                    .changes
+                    .push(line.to_owned());
+            }
+        }
+
+        Ok(Self { releases })
+    }
+}
+
+/// A change before the first version line of a changelog, by its line index
+#[derive(Clone, Copy, Debug)]
+pub struct ParseChangelogError(pub usize);
+
+impl Display for ParseChangelogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} is not part of a version", self.0 + 1)
+    }
+}
+
+impl std::error::Error for ParseChangelogError {}
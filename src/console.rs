@@ -0,0 +1,525 @@
+use std::fs;
+
+use macroquad::{
+    color::{Color, colors},
+    input::{self, KeyCode},
+    shapes,
+};
+
+use inverse::debug_flags::DebugFlags;
+use inverse::editor::Editor;
+use inverse::level::{LevelChange, Levels};
+use inverse::physics::PhysicsOverrides;
+use inverse::player::Player;
+use inverse::savegame::SaveGame;
+use inverse::storage::TrackedFile;
+
+use crate::debug::DebugOverlay;
+use crate::text::{self, TextSize};
+
+const KEEP_LINES: usize = 64;
+const VISIBLE_LINES: usize = 8;
+const LINE_HEIGHT: f32 = TextSize::Small.height() * 1.25;
+
+/// A drop-down command line for developers, opened with the grave key
+pub struct Console {
+    pub open: bool,
+    input: String,
+    /// The last command run, which can be brought back with the up key
+    last_input: String,
+    /// Commands and their output, oldest first
+    lines: Vec<String>,
+    commands: Vec<Command>,
+}
+
+/// A command which can be run from the console, as its name followed by arguments separated by
+/// whitespace
+pub struct Command {
+    pub name: &'static str,
+    /// The arguments the command takes, shown by `help`
+    pub usage: &'static str,
+    /// Runs the command with its arguments, returning a message for the console either way
+    pub run: fn(&[&str], &mut Context) -> Result<String, String>,
+}
+
+/// The parts of the game commands can change
+pub struct Context<'a> {
+    pub levels: &'a mut Levels,
+    pub player: &'a mut Player,
+    pub editor: &'a mut Editor,
+    pub editor_enabled: &'a mut bool,
+    pub savegame: &'a mut SaveGame,
+    pub levels_file: &'a mut TrackedFile,
+    pub save_file: &'a mut TrackedFile,
+    pub debug_overlay: &'a mut DebugOverlay,
+    /// Set by commands to reload the game from the levels file once the console has been updated
+    pub reload: bool,
+    /// Set by commands which change the levels without writing them, so they're autosaved
+    pub levels_changed: bool,
+}
+
+impl Console {
+    /// Creates a closed console with the built in commands
+    #[must_use]
+    pub fn new() -> Self {
+        let mut console = Self {
+            open: false,
+            input: String::new(),
+            last_input: String::new(),
+            lines: Vec::new(),
+            commands: Vec::new(),
+        };
+
+        for command in built_in_commands() {
+            console.register(command);
+        }
+
+        console
+    }
+
+    /// Adds a command, replacing any command with the same name
+    pub fn register(&mut self, command: Command) {
+        self.commands
+            .retain(|existing| existing.name != command.name);
+        self.commands.push(command);
+    }
+
+    /// Handles typing into the console while it's open, running the command once enter is pressed
+    pub fn update(&mut self, context: &mut Context) {
+        while let Some(character) = input::get_char_pressed() {
+            // The key which opens the console shouldn't be typed into it
+            if !character.is_control() && character != '`' && character != '~' {
+                self.input.push(character);
+            }
+        }
+
+        if input::is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+
+        if input::is_key_pressed(KeyCode::Up) {
+            self.input = self.last_input.clone();
+        }
+
+        if input::is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        }
+
+        if input::is_key_pressed(KeyCode::Enter) && !self.input.trim().is_empty() {
+            let line = std::mem::take(&mut self.input);
+
+            self.run(&line, context);
+            self.last_input = line;
+        }
+    }
+
+    /// Runs a line of input as a command, writing it and its result to the console
+    pub fn run(&mut self, line: &str, context: &mut Context) {
+        self.print(format!("> {line}"));
+
+        let mut words = line.split_whitespace();
+
+        let Some(name) = words.next() else {
+            return;
+        };
+
+        let arguments = words.collect::<Vec<_>>();
+
+        let result = if name == "help" {
+            Ok(self
+                .commands
+                .iter()
+                .map(|command| format!("{} {}", command.name, command.usage))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        } else if let Some(command) = self.commands.iter().find(|command| command.name == name) {
+            (command.run)(&arguments, context)
+                .map_err(|error| format!("{error}\nusage: {} {}", command.name, command.usage))
+        } else {
+            Err(format!("unknown command `{name}`, try `help`"))
+        };
+
+        match result {
+            Ok(message) => self.print(message),
+            Err(message) => self.print(format!("error: {message}")),
+        }
+    }
+
+    fn print(&mut self, message: String) {
+        self.lines.extend(message.lines().map(str::to_owned));
+
+        let excess = self.lines.len().saturating_sub(KEEP_LINES);
+        self.lines.drain(..excess);
+    }
+
+    /// Draws the console over the top of the window
+    pub fn draw(&self, width: f32, height: f32) {
+        let console_height = (VISIBLE_LINES + 1) as f32 * LINE_HEIGHT + LINE_HEIGHT / 2.0;
+        let left = -width / 2.0 + LINE_HEIGHT / 2.0;
+        let bottom = height / 2.0 - console_height;
+
+        shapes::draw_rectangle(
+            -width / 2.0,
+            bottom,
+            width,
+            console_height,
+            Color {
+                a: 0.875,
+                ..colors::BLACK
+            },
+        );
+
+        let prompt_y = bottom + LINE_HEIGHT * 0.75;
+
+        text::draw_left_aligned(
+            &format!("> {}_", self.input),
+            [left, prompt_y],
+            TextSize::Small,
+            colors::WHITE,
+        );
+
+        for (i, line) in self.lines.iter().rev().take(VISIBLE_LINES).enumerate() {
+            text::draw_left_aligned(
+                line,
+                [left, prompt_y + (i + 1) as f32 * LINE_HEIGHT],
+                TextSize::Small,
+                colors::GRAY,
+            );
+        }
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn built_in_commands() -> Vec<Command> {
+    vec![
+        Command {
+            name: "level",
+            usage: "<number>",
+            run: |arguments, context| {
+                let [number] = arguments else {
+                    return Err("expected a level number".to_owned());
+                };
+
+                let number = number
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|number| (1..=context.levels.num_levels).contains(number))
+                    .ok_or(format!(
+                        "levels are numbered from 1 to {}",
+                        context.levels.num_levels
+                    ))?;
+
+                context.editor.force_undo_temporary_actions(context.levels);
+                context.player.enter_level(context.levels, number - 1);
+
+                Ok(format!("entered level {number}"))
+            },
+        },
+        Command {
+            name: "tp",
+            usage: "<x> <y>",
+            run: |arguments, context| {
+                let [x, y] = arguments else {
+                    return Err("expected a position".to_owned());
+                };
+
+                let position = [x, y].map(|coordinate| {
+                    coordinate
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|coordinate| coordinate.is_finite())
+                });
+
+                let [Some(x), Some(y)] = position else {
+                    return Err("positions are numbers of tiles from the bottom left".to_owned());
+                };
+
+                let old_position = context.player.position;
+                context.player.position = [x, y];
+
+                if context.player.is_intersecting(context.levels) {
+                    context.player.position = old_position;
+                    return Err("the player doesn't fit there".to_owned());
+                }
+
+                context.player.previous_position = context.player.position;
+                context.player.velocity = [0.0, 0.0];
+
+                Ok(format!("moved to {x} {y}"))
+            },
+        },
+        Command {
+            name: "give",
+            usage: "gem <limited|full>",
+            run: |arguments, context| {
+                match arguments {
+                    ["gem", "limited"] => {
+                        context.savegame.limited_gem_collected = true;
+                        *context.editor_enabled = true;
+                    }
+                    ["gem", "full"] => {
+                        context.savegame.full_gem_collected = true;
+                        *context.editor_enabled = true;
+
+                        context.editor.force_undo_temporary_actions(context.levels);
+                        *context.editor = Editor::Full { drag: None };
+                    }
+                    _ => return Err("expected a gem to give".to_owned()),
+                }
+
+                // Saving is best effort, like everywhere else
+                let _ = context.savegame.save(context.save_file);
+
+                Ok(format!("gave {} gem", arguments[1]))
+            },
+        },
+        Command {
+            name: "set",
+            usage: "<gravity|friction|jump> <scale>",
+            run: |arguments, context| {
+                let [name, scale] = arguments else {
+                    return Err("expected a setting and a scale".to_owned());
+                };
+
+                let scale = scale
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|scale| PhysicsOverrides::is_valid_scale(*scale))
+                    .ok_or("the scale must be a positive number".to_owned())?;
+
+                let overrides = &mut context.levels.metadata[context.levels.level_index].physics;
+
+                match *name {
+                    "gravity" => overrides.gravity_scale = Some(scale),
+                    "friction" => overrides.friction_scale = Some(scale),
+                    "jump" => overrides.jump_scale = Some(scale),
+                    _ => return Err(format!("unknown setting `{name}`")),
+                }
+
+                // The overrides are part of the level, so they're saved along with it
+                context.levels_changed = true;
+
+                Ok(format!("set {name} to {scale} in this level"))
+            },
+        },
+        Command {
+            name: "editor",
+            usage: "<off|limited|full>",
+            run: |arguments, context| {
+                let [mode] = arguments else {
+                    return Err("expected an editor".to_owned());
+                };
+
+                context.editor.force_undo_temporary_actions(context.levels);
+
+                match *mode {
+                    "off" => *context.editor_enabled = false,
+                    "limited" => {
+                        *context.editor_enabled = true;
+                        *context.editor = Editor::Limited {
+                            last_selected: None,
+                        };
+                    }
+                    "full" => {
+                        *context.editor_enabled = true;
+                        *context.editor = Editor::Full { drag: None };
+                    }
+                    _ => return Err(format!("unknown editor `{mode}`")),
+                }
+
+                Ok(format!("editor {mode}"))
+            },
+        },
+        Command {
+            name: "insert_level",
+            usage: "",
+            run: |_, context| {
+                let index = context.levels.level_index + 1;
+
+                let change = context.levels.insert_level(index);
+                remap_progress(context, change);
+                write_levels(context)?;
+
+                Ok(format!("inserted level {}", index + 1))
+            },
+        },
+        Command {
+            name: "remove_level",
+            usage: "",
+            run: |_, context| {
+                if context.levels.num_levels <= 1 {
+                    return Err("the last level can't be removed".to_owned());
+                }
+
+                let index = context.levels.level_index;
+
+                context.editor.force_undo_temporary_actions(context.levels);
+
+                let change = context.levels.remove_level(index);
+                remap_progress(context, change);
+                context
+                    .player
+                    .enter_level(context.levels, context.levels.level_index);
+                write_levels(context)?;
+
+                Ok(format!("removed level {}", index + 1))
+            },
+        },
+        Command {
+            name: "reload",
+            usage: "",
+            run: |_, context| {
+                context.reload = true;
+
+                Ok("reloading the levels file".to_owned())
+            },
+        },
+        Command {
+            name: "restore_backup",
+            usage: "[number]",
+            run: |arguments, context| {
+                let number = match arguments {
+                    [] => 1,
+                    [number] => number
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|number| (1..=TrackedFile::BACKUPS).contains(number))
+                        .ok_or(format!(
+                            "backups are numbered from 1 to {}, newest first",
+                            TrackedFile::BACKUPS
+                        ))?,
+                    _ => return Err("expected at most a backup number".to_owned()),
+                };
+
+                context
+                    .levels_file
+                    .restore_backup(number)
+                    .map_err(|error| error.to_string())?;
+                context.reload = true;
+
+                Ok(format!(
+                    "restored backup {number}, the replaced levels are now backup 1"
+                ))
+            },
+        },
+        Command {
+            name: "validate",
+            usage: "",
+            run: |_, context| {
+                // Read the file directly, so that reading it doesn't hide changes made by
+                // someone else from the conflict check when the levels are next written
+                let text = fs::read_to_string(&context.levels_file.path)
+                    .map_err(|error| error.to_string())?;
+
+                let levels = Levels::parse_located(&text).map_err(|error| error.to_string())?;
+
+                if let Some(problem) = levels.problems().first() {
+                    return Err(problem.to_string());
+                }
+
+                Ok(format!(
+                    "the levels file is valid, with {} levels",
+                    levels.num_levels
+                ))
+            },
+        },
+        Command {
+            name: "debug",
+            usage: "[flag]",
+            run: |arguments, context| {
+                let flags = &mut context.savegame.debug_flags;
+
+                match arguments {
+                    [] => {}
+                    [name] => {
+                        let flag = flags.get_mut(name).ok_or(format!(
+                            "unknown flag `{name}`, the flags are {}",
+                            DebugFlags::NAMES.join(" ")
+                        ))?;
+
+                        *flag ^= true;
+
+                        // Saving is best effort, like everywhere else
+                        let _ = context.savegame.save(context.save_file);
+                    }
+                    _ => return Err("expected at most one flag".to_owned()),
+                }
+
+                let enabled = context.savegame.debug_flags.enabled().collect::<Vec<_>>();
+
+                if enabled.is_empty() {
+                    Ok("no debug flags are on".to_owned())
+                } else {
+                    Ok(format!("debug flags on: {}", enabled.join(" ")))
+                }
+            },
+        },
+        Command {
+            name: "fly",
+            usage: "[tiles per second]",
+            run: |arguments, context| {
+                let flags = &mut context.savegame.debug_flags;
+
+                match arguments {
+                    [] => flags.noclip ^= true,
+                    [speed] => {
+                        context.player.fly_speed = speed
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|speed| *speed > 0.0)
+                            .ok_or("the speed must be a positive number".to_owned())?;
+
+                        flags.noclip = true;
+                    }
+                    _ => return Err("expected at most a speed".to_owned()),
+                }
+
+                let _ = context.savegame.save(context.save_file);
+
+                if context.savegame.debug_flags.noclip {
+                    Ok(format!(
+                        "flying at {} tiles per second",
+                        context.player.fly_speed
+                    ))
+                } else {
+                    Ok("stopped flying".to_owned())
+                }
+            },
+        },
+        Command {
+            name: "overlay",
+            usage: "",
+            run: |_, context| {
+                context.debug_overlay.enabled ^= true;
+
+                Ok(format!(
+                    "debug overlay {}",
+                    if context.debug_overlay.enabled {
+                        "on"
+                    } else {
+                        "off"
+                    }
+                ))
+            },
+        },
+    ]
+}
+
+/// Moves the player's progress along with the levels after they've been rearranged
+fn remap_progress(context: &mut Context, change: LevelChange) {
+    context.player.remap_levels(change);
+    context.savegame.remap_levels(change);
+}
+
+fn write_levels(context: &mut Context) -> Result<(), String> {
+    context
+        .levels_file
+        .write(context.levels.to_string())
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
@@ -0,0 +1,248 @@
+use macroquad::{
+    color::colors,
+    input::{self, KeyCode},
+};
+
+use inverse::keybinds::{self, Action, Binding, Context, Keybinds, Modifiers, Preset};
+use inverse::storage::TrackedFile;
+
+use crate::text::{self, TextSize};
+use crate::ui::{Menu, MenuState};
+
+/// The pages of the controls menu, which are the contexts, with the level list's actions on a page
+/// of their own so that the editor's fit on the screen
+const PAGES: [(Context, bool); 4] = [
+    (Context::Gameplay, false),
+    (Context::Editor, false),
+    (Context::Editor, true),
+    (Context::Global, false),
+];
+
+/// The page of the pause menu for changing keybinds, which shows the actions of one context at a
+/// time
+#[derive(Clone, Debug)]
+pub struct ControlsMenu {
+    pub menu: MenuState,
+    /// The index of the page being shown in [`PAGES`]
+    page: usize,
+    /// The action whose keys are being changed, if any
+    pub rebinding: Option<Action>,
+    /// A modifier key pressed while rebinding, which is bound on its own if it's let go before
+    /// another key is pressed, or held for a chord with that key if not
+    held_modifier: Option<KeyCode>,
+    /// The preset which suits the player's keyboard, if it isn't the QWERTY one
+    suggested_preset: Option<Preset>,
+}
+
+impl Default for ControlsMenu {
+    fn default() -> Self {
+        Self {
+            menu: MenuState::default(),
+            page: 0,
+            rebinding: None,
+            held_modifier: None,
+            suggested_preset: Preset::suggested(),
+        }
+    }
+}
+
+impl ControlsMenu {
+    /// Draws the menu and handles its input, saving the keybinds as soon as they change. Returns
+    /// `true` once the player leaves the menu.
+    pub fn update(&mut self, keybinds: &mut Keybinds, keybinds_file: &mut TrackedFile) -> bool {
+        if let Some(action) = self.rebinding {
+            self.update_rebinding(action, keybinds, keybinds_file);
+
+            return false;
+        }
+
+        let (context, level_list) = PAGES[self.page];
+
+        let mut menu = Menu::begin(&mut self.menu, keybinds);
+
+        let next_page = menu.button(&format!(
+            "{:<14}{}",
+            "SHOWING",
+            page_label(context, level_list)
+        ));
+
+        // Presets only change the gameplay keys, so they're picked from that page
+        let next_preset = context == Context::Gameplay
+            && menu.button(&format!(
+                "{:<14}{}",
+                "PRESET",
+                preset_label(keybinds.preset(), self.suggested_preset)
+            ));
+
+        for action in context
+            .actions()
+            .filter(|action| action.is_in_level_list() == level_list)
+        {
+            let mut label = action_label(action);
+
+            if !keybinds.conflicts(action).is_empty() {
+                label.push_str(" !");
+            }
+
+            if menu.button(&format!("{label:<14}{}", keys_label(keybinds, action))) {
+                self.rebinding = Some(action);
+            }
+        }
+
+        let back = menu.button("BACK");
+
+        menu.end();
+
+        if next_preset {
+            let index = keybinds.preset().map_or(0, |preset| {
+                let index = Preset::ALL
+                    .iter()
+                    .position(|other| *other == preset)
+                    .unwrap();
+
+                (index + 1) % Preset::ALL.len()
+            });
+
+            keybinds.apply_preset(Preset::ALL[index]);
+            let _ = keybinds.save(keybinds_file);
+        }
+
+        if next_page {
+            self.page = (self.page + 1) % PAGES.len();
+        }
+
+        back || input::is_key_pressed(KeyCode::Escape)
+    }
+
+    fn update_rebinding(
+        &mut self,
+        action: Action,
+        keybinds: &mut Keybinds,
+        keybinds_file: &mut TrackedFile,
+    ) {
+        text::draw_centered(
+            &format!("PRESS KEYS FOR {}", action_label(action)),
+            [0.0, 1.0],
+            TextSize::Medium,
+            colors::WHITE,
+        );
+        text::draw_centered(
+            &keys_label(keybinds, action),
+            [0.0, 0.0],
+            TextSize::Medium,
+            colors::WHITE,
+        );
+
+        let conflicts = keybinds.conflicts(action);
+
+        if !conflicts.is_empty() {
+            let names = conflicts
+                .into_iter()
+                .map(action_label)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            text::draw_centered(
+                &format!("ALSO USED BY {names}"),
+                [0.0, -1.0],
+                TextSize::Small,
+                colors::RED,
+            );
+        }
+
+        text::draw_centered(
+            "ESCAPE TO FINISH",
+            [0.0, -2.0],
+            TextSize::Small,
+            colors::WHITE,
+        );
+
+        let mut binding = None;
+
+        if let Some(modifier) = self.held_modifier
+            && input::is_key_released(modifier)
+        {
+            self.held_modifier = None;
+            binding = Some(Binding::from(modifier));
+        }
+
+        match input::get_last_key_pressed() {
+            Some(KeyCode::Escape) => {
+                self.rebinding = None;
+                self.held_modifier = None;
+            }
+            Some(key) if Modifiers::is_modifier_key(key) => self.held_modifier = Some(key),
+            Some(key) => {
+                self.held_modifier = None;
+                binding = Some(Binding {
+                    modifiers: Modifiers::held(),
+                    key,
+                });
+            }
+            None => {}
+        }
+
+        // The keybinds still work for this session if they can't be saved
+        if let Some(binding) = binding
+            && keybinds.toggle_binding(action, binding)
+        {
+            let _ = keybinds.save(keybinds_file);
+        }
+    }
+}
+
+fn page_label(context: Context, level_list: bool) -> String {
+    if level_list {
+        "LEVEL LIST".to_owned()
+    } else {
+        context.name().to_uppercase()
+    }
+}
+
+/// The name of the preset in use, or `CUSTOM` if there isn't one, along with the suggested
+/// preset if it's a different one
+fn preset_label(preset: Option<Preset>, suggested: Option<Preset>) -> String {
+    let name = |preset: Preset| preset.name().replace('_', " ").to_uppercase();
+
+    let label = preset.map_or("CUSTOM".to_owned(), name);
+
+    match suggested {
+        Some(suggested) if preset != Some(suggested) => {
+            format!("{label}, TRY {}", name(suggested))
+        }
+        _ => label,
+    }
+}
+
+fn action_label(action: Action) -> String {
+    action.name().replace('_', " ").to_uppercase()
+}
+
+fn keys_label(keybinds: &Keybinds, action: Action) -> String {
+    let keys = keybinds
+        .bindings(action)
+        .iter()
+        .map(|binding| binding_label(*binding))
+        .collect::<Vec<_>>();
+
+    if keys.is_empty() {
+        "NONE".to_owned()
+    } else {
+        keys.join(" ")
+    }
+}
+
+/// The binding as it's written in the keybinds file, with modifier keys shortened so that the
+/// labels fit in the menu
+pub fn binding_label(binding: Binding) -> String {
+    let label = if Modifiers::is_modifier_key(binding.key) {
+        keybinds::key_name(binding.key)
+            .replace("Left", "L")
+            .replace("Right", "R")
+            .replace("Control", "Ctrl")
+    } else {
+        binding.to_string()
+    };
+
+    label.to_uppercase()
+}
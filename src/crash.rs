@@ -0,0 +1,130 @@
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    fs, panic,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use inverse::{level::Levels, player::Player, storage};
+
+use crate::LEVELS_FILE_NAME;
+
+/// The number of frames of input kept for crash dumps
+const INPUT_HISTORY_LENGTH: usize = 120;
+
+static STATE: Mutex<Option<CrashState>> = Mutex::new(None);
+
+/// The last known state of the game, written to a dump file if the game panics
+#[derive(Clone, Debug)]
+pub struct CrashState {
+    pub levels: Levels,
+    /// Whether or not the levels have edits which haven't been saved yet
+    pub unsaved_levels: bool,
+    pub player: Player,
+    pub inputs: VecDeque<[bool; 4]>,
+}
+
+/// Installs a panic hook which writes the last recorded state to a crash dump in the data
+/// directory, along with any unsaved level edits, before running the default hook
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        // The state may be locked by the panicking thread, in which case it's not safe to read
+        if let Ok(state) = STATE.try_lock()
+            && let Some(state) = &*state
+        {
+            let path = storage::data_directory().join(format!(
+                "crash-{}.txt",
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_secs()),
+            ));
+
+            if fs::write(&path, state.dump(&info.to_string())).is_ok() {
+                eprintln!(
+                    "Inverse crashed, details were written to {}",
+                    path.display()
+                );
+            }
+
+            // The levels file itself is left alone, in case the levels are what caused the crash
+            if state.unsaved_levels {
+                let path = storage::data_directory().join(format!("{LEVELS_FILE_NAME}.recovered"));
+
+                if fs::write(&path, state.levels.to_string()).is_ok() {
+                    eprintln!("Unsaved level edits were written to {}", path.display());
+                }
+            }
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Records the inputs held during a frame
+pub fn record_input(inputs: [bool; 4]) {
+    if let Ok(mut state) = STATE.lock()
+        && let Some(state) = &mut *state
+    {
+        if state.inputs.len() >= INPUT_HISTORY_LENGTH {
+            state.inputs.pop_front();
+        }
+
+        state.inputs.push_back(inputs);
+    }
+}
+
+/// Records the state of the game at the end of a frame
+pub fn record_state(levels: &Levels, player: &Player, unsaved_levels: bool) {
+    if let Ok(mut state) = STATE.lock() {
+        match &mut *state {
+            Some(state) => {
+                state.levels.clone_from(levels);
+                state.unsaved_levels = unsaved_levels;
+                state.player.clone_from(player);
+            }
+            None => {
+                *state = Some(CrashState {
+                    levels: levels.clone(),
+                    unsaved_levels,
+                    player: player.clone(),
+                    inputs: VecDeque::with_capacity(INPUT_HISTORY_LENGTH),
+                });
+            }
+        }
+    }
+}
+
+impl CrashState {
+    pub fn dump(&self, message: &str) -> String {
+        let mut dump = String::new();
+
+        // Writing to a string can't fail
+        let _ = writeln!(dump, "{message}\n");
+        let _ = writeln!(
+            dump,
+            "Level {} of {}",
+            self.levels.level_index + 1,
+            self.levels.num_levels,
+        );
+        let _ = writeln!(dump, "{:#?}\n", self.player);
+
+        let _ = writeln!(dump, "Inputs (up, left, down, right), oldest first:");
+        for inputs in &self.inputs {
+            let _ = writeln!(
+                dump,
+                "{}",
+                inputs
+                    .map(|input| if input { '#' } else { '.' })
+                    .iter()
+                    .collect::<String>(),
+            );
+        }
+
+        let _ = writeln!(dump, "\nLevels:\n{}", self.levels);
+
+        dump
+    }
+}
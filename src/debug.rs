@@ -0,0 +1,66 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use macroquad::color::colors;
+
+use inverse::{level::Levels, player::Player};
+
+use crate::text::{self, TextSize};
+
+/// Shows the simulation tick, state hash and player position in the top left corner of the
+/// window, so runs can be compared to find the first tick where they diverge, and positions can be
+/// noted to get back to with the console
+#[derive(Clone, Debug, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+    pub tick: u64,
+    pub state_hash: u64,
+    pub player_position: [f32; 2],
+}
+
+impl DebugOverlay {
+    /// Records the state after a tick of the simulation
+    pub fn record_tick(&mut self, levels: &Levels, player: &Player) {
+        self.tick += 1;
+        self.state_hash = state_hash(levels, player);
+        self.player_position = player.position;
+    }
+
+    pub fn draw(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        let line_height = TextSize::Small.height() * 1.5 * text::screen_pixels_per_unit();
+
+        for (i, line) in [
+            format!("TICK {}", self.tick),
+            format!("HASH {:016x}", self.state_hash),
+            format!(
+                "POS {:.3} {:.3}",
+                self.player_position[0], self.player_position[1]
+            ),
+        ]
+        .iter()
+        .enumerate()
+        {
+            text::draw_left_aligned_on_screen(
+                line,
+                [line_height / 2.0, (i as f32 + 0.5) * line_height],
+                TextSize::Small,
+                colors::RED,
+            );
+        }
+    }
+}
+
+/// A cheap hash of everything which affects the simulation. It's stable between runs of the same
+/// build, but not necessarily between builds.
+#[must_use]
+pub fn state_hash(levels: &Levels, player: &Player) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    levels.hash(&mut hasher);
+    player.hash(&mut hasher);
+
+    hasher.finish()
+}
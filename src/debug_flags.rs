@@ -0,0 +1,48 @@
+/// Developer options which change how the game plays. Times set while any of them are on are
+/// marked as debug times in the save, so they don't count as real records.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DebugFlags {
+    /// The player flies through everything instead of colliding with it
+    pub noclip: bool,
+    /// Spikes don't kill the player
+    pub invincible: bool,
+    /// Collision boxes and pickup ranges are drawn over the level
+    pub hitboxes: bool,
+    /// The full editor is always unlocked
+    pub free_editor: bool,
+}
+
+impl DebugFlags {
+    /// The names of the flags in the console and the save file
+    pub const NAMES: [&'static str; 4] = ["noclip", "invincible", "hitboxes", "free_editor"];
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let mut flags = *self;
+
+        flags.get_mut(name).map(|flag| *flag)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "noclip" => Some(&mut self.noclip),
+            "invincible" => Some(&mut self.invincible),
+            "hitboxes" => Some(&mut self.hitboxes),
+            "free_editor" => Some(&mut self.free_editor),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if any of the flags are on
+    #[must_use]
+    pub fn any(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// The names of the flags which are on
+    pub fn enabled(&self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(|name| self.get(name) == Some(true))
+    }
+}
@@ -0,0 +1,87 @@
+use macroquad::{color::Color, shapes};
+
+/// The segments of a digit as `[x, y, width, height]` on a grid 3 segment thicknesses wide and
+/// 5 tall, with the origin at the bottom left. In order, they are the top, top right, bottom
+/// right, bottom, bottom left, top left, and middle segments.
+const SEGMENTS: [[f32; 4]; 7] = [
+    [0.0, 4.0, 3.0, 1.0],
+    [2.0, 2.0, 1.0, 3.0],
+    [2.0, 0.0, 1.0, 3.0],
+    [0.0, 0.0, 3.0, 1.0],
+    [0.0, 0.0, 1.0, 3.0],
+    [0.0, 2.0, 1.0, 3.0],
+    [0.0, 2.0, 3.0, 1.0],
+];
+
+/// Which of the [`SEGMENTS`] are lit for each digit, as bit flags
+const DIGITS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+
+const GRID_WIDTH: f32 = 3.0;
+const GRID_HEIGHT: f32 = 5.0;
+const SPACING: f32 = 1.0;
+
+/// Draws a number as segment style digits made of rectangles, with the top left corner at
+/// `position` in level space. Each part of a segment is colored by `color_at` according to the
+/// level tile it lies over, so the digits can contrast with whatever is behind them.
+pub fn draw_number(
+    number: usize,
+    position: [f32; 2],
+    height: f32,
+    color_at: impl Fn([f32; 2]) -> Color,
+) {
+    let thickness = height / GRID_HEIGHT;
+    let bottom = position[1] - height;
+
+    for (i, digit) in number.to_string().bytes().enumerate() {
+        let left = position[0] + i as f32 * (GRID_WIDTH + SPACING) * thickness;
+        let segments = DIGITS[(digit - b'0') as usize];
+
+        for (j, segment) in SEGMENTS.into_iter().enumerate() {
+            if segments & (1 << j) == 0 {
+                continue;
+            }
+
+            fill_rectangle(
+                [
+                    left + segment[0] * thickness,
+                    bottom + segment[1] * thickness,
+                    segment[2] * thickness,
+                    segment[3] * thickness,
+                ],
+                &color_at,
+            );
+        }
+    }
+}
+
+/// Fills a rectangle in level space, split along tile boundaries so each piece can be colored
+/// separately
+fn fill_rectangle(rectangle: [f32; 4], color_at: &impl Fn([f32; 2]) -> Color) {
+    let [x, y, width, height] = rectangle;
+
+    let mut left = x;
+
+    while left < x + width {
+        let right = (left.floor() + 1.0).min(x + width);
+        let mut bottom = y;
+
+        while bottom < y + height {
+            let top = (bottom.floor() + 1.0).min(y + height);
+
+            shapes::draw_rectangle(
+                left - crate::LOGICAL_SCREEN_WIDTH / 2.0,
+                bottom - crate::LOGICAL_SCREEN_HEIGHT / 2.0,
+                right - left,
+                top - bottom,
+                color_at([(left + right) / 2.0, (bottom + top) / 2.0]),
+            );
+
+            bottom = top;
+        }
+
+        left = right;
+    }
+}
@@ -0,0 +1,472 @@
+use crate::level::Levels;
+use crate::pickup::Pickup;
+use crate::player::Player;
+use crate::tile::Tile;
+
+#[derive(Clone, Debug)]
+pub enum Editor {
+    Limited { last_selected: Option<usize> },
+    Full { drag: Option<Drag> },
+}
+
+/// A shape being dragged out in the full editor, which is only applied once the mouse is released.
+/// Positions are of tiles on the screen, like the indices of [`Levels`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Drag {
+    pub start: [usize; 2],
+    pub end: [usize; 2],
+    pub shape: Shape,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    /// A filled rectangle with the start and end in opposite corners
+    Rectangle,
+    Line,
+    /// A rectangle like [`Shape::Rectangle`] which selects the tiles in it to copy, rather than
+    /// filling them
+    Selection,
+}
+
+/// One of the two gems, which unlock the editors when touched
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gem {
+    Limited,
+    Full,
+}
+
+/// The full editor's copy and paste. The copied tiles are kept when moving between levels, so they
+/// can be pasted into any level.
+#[derive(Clone, Debug, Default)]
+pub struct Clipboard {
+    /// The tiles which would be copied, as a drag with the [`Shape::Selection`] shape
+    pub selection: Option<Drag>,
+    pub copied: Option<CopiedTiles>,
+    /// Whether the copied tiles are following the mouse, waiting to be pasted
+    pub pasting: bool,
+}
+
+/// A rectangle of tiles copied in the full editor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopiedTiles {
+    /// The width and height of the rectangle
+    pub size: [usize; 2],
+    /// The tiles column by column from the left, each from the bottom, like [`Levels::tiles`]
+    pub tiles: Vec<Tile>,
+}
+
+impl Editor {
+    /// Returns whether or not to write the changes made
+    #[must_use]
+    pub fn toggle_tile_index(
+        &mut self,
+        tile_index: usize,
+        levels: &mut Levels,
+        player: &mut Player,
+    ) -> bool {
+        profile_scope!("editor");
+
+        if is_under_gem(tile_index, levels) {
+            return false;
+        }
+
+        let old_tile = levels.tiles[tile_index];
+
+        // The limited editor undoes its changes by toggling tiles back, so it can only change
+        // tiles which come back the same
+        if let Editor::Limited { .. } = self
+            && (levels.level_index == levels.num_levels - 1
+                || tile_index < Levels::LEVEL_HEIGHT
+                || !old_tile.is_plain())
+        {
+            return false;
+        }
+
+        levels.tiles[tile_index] = old_tile.toggled();
+
+        let write = match self {
+            Editor::Limited { last_selected } => {
+                if *last_selected == Some(tile_index) {
+                    *last_selected = None;
+                } else if let Some(last_selected) = last_selected {
+                    toggle(&mut levels.tiles[*last_selected]);
+                    *last_selected = tile_index;
+                } else {
+                    *last_selected = Some(tile_index);
+                }
+
+                false
+            }
+            Editor::Full { .. } => true,
+        };
+
+        player.resolve_crush(levels);
+
+        write
+    }
+
+    /// Starts dragging out a shape in the full editor
+    pub fn start_drag(&mut self, position: [usize; 2], shape: Shape) {
+        if let Editor::Full { drag } = self {
+            *drag = Some(Drag {
+                start: position,
+                end: position,
+                shape,
+            });
+        }
+    }
+
+    /// Fills the shape being dragged out with the opposite of the tile it started on, or toggles
+    /// that tile if the drag never left it. Returns whether or not to write the changes made.
+    #[must_use]
+    pub fn finish_drag(&mut self, levels: &mut Levels, player: &mut Player) -> bool {
+        let Editor::Full { drag } = self else {
+            return false;
+        };
+
+        let Some(drag) = drag.take() else {
+            return false;
+        };
+
+        if drag.shape == Shape::Selection {
+            return false;
+        }
+
+        let Some(start_index) = levels.index_of(drag.start) else {
+            return false;
+        };
+
+        if drag.start == drag.end {
+            return self.toggle_tile_index(start_index, levels, player);
+        }
+
+        profile_scope!("editor");
+
+        let tile = levels.tiles[start_index].toggled();
+        let mut changed = false;
+
+        for position in drag.positions() {
+            let Some(tile_index) = levels.index_of(position) else {
+                continue;
+            };
+
+            if is_under_gem(tile_index, levels) {
+                continue;
+            }
+
+            levels.tiles[tile_index] = tile;
+            changed = true;
+        }
+
+        player.resolve_crush(levels);
+
+        changed
+    }
+
+    /// Stops dragging out a selection in the full editor, returning it. Other shapes are left for
+    /// [`Editor::finish_drag`].
+    pub fn finish_selection(&mut self) -> Option<Drag> {
+        let Editor::Full { drag } = self else {
+            return None;
+        };
+
+        drag.take_if(|drag| drag.shape == Shape::Selection)
+    }
+
+    /// Pastes the copied tiles with their bottom left corner at `corner`, leaving out any which
+    /// would go off the screen, and stops pasting. Only the full editor can do this. Returns
+    /// whether or not to write the changes made.
+    #[must_use]
+    pub fn paste(
+        &mut self,
+        corner: [usize; 2],
+        clipboard: &mut Clipboard,
+        levels: &mut Levels,
+        player: &mut Player,
+    ) -> bool {
+        if !self.is_full() || !clipboard.pasting {
+            return false;
+        }
+
+        clipboard.pasting = false;
+
+        profile_scope!("editor");
+
+        let mut changed = false;
+
+        for (position, tile) in clipboard.paste_positions(corner) {
+            let Some(tile_index) = levels.index_of(position) else {
+                continue;
+            };
+
+            if is_under_gem(tile_index, levels) {
+                continue;
+            }
+
+            levels.tiles[tile_index] = tile;
+            changed = true;
+        }
+
+        player.resolve_crush(levels);
+
+        changed
+    }
+
+    /// Moves a gem so that it rests in the tile at `tile_index`, placing it if it isn't in the
+    /// levels, or removes it if it's already there. It can only go where [`can_place_gem`] allows.
+    /// Only the full editor can do this. Returns whether or not to write the changes made.
+    #[must_use]
+    pub fn place_gem(
+        &mut self,
+        gem: Gem,
+        tile_index: usize,
+        levels: &mut Levels,
+        player: &Player,
+    ) -> bool {
+        if !self.is_full() {
+            return false;
+        }
+
+        let current_index = match gem {
+            Gem::Limited => levels.limited_gem,
+            Gem::Full => levels.full_gem,
+        };
+
+        let new_index = if current_index == Some(tile_index) {
+            None
+        } else if can_place_gem(tile_index, levels, player) {
+            Some(tile_index)
+        } else {
+            return false;
+        };
+
+        match gem {
+            Gem::Limited => levels.limited_gem = new_index,
+            Gem::Full => levels.full_gem = new_index,
+        }
+
+        true
+    }
+
+    /// Places a tile with its own behavior, or clears it if it is already there. Only the full
+    /// editor can do this. Returns whether or not to write the changes made.
+    #[must_use]
+    pub fn place_tile(
+        &mut self,
+        tile_index: usize,
+        tile: Tile,
+        levels: &mut Levels,
+        player: &mut Player,
+    ) -> bool {
+        if !self.is_full() || is_under_gem(tile_index, levels) {
+            return false;
+        }
+
+        let old_tile = levels.tiles[tile_index];
+
+        levels.tiles[tile_index] = if old_tile == tile { Tile::Empty } else { tile };
+
+        player.resolve_crush(levels);
+
+        true
+    }
+
+    /// Sends the player back to where they entered the current level, forgetting its checkpoint.
+    /// Any temporary changes are undone first, but which editor is unlocked stays the same.
+    pub fn restart_level(&mut self, levels: &mut Levels, player: &mut Player) {
+        self.force_undo_temporary_actions(levels);
+
+        player.checkpoints.remove(&levels.level_index);
+        player.inputs_down = [false; 4];
+        player.inputs_ready = [false; 4];
+        player.respawn(levels);
+    }
+
+    pub fn force_undo_temporary_actions(&mut self, levels: &mut Levels) {
+        match self {
+            Editor::Limited { last_selected } => {
+                if let Some(tile_index) = *last_selected {
+                    toggle(&mut levels.tiles[tile_index]);
+                    *last_selected = None;
+                }
+            }
+            Editor::Full { drag } => *drag = None,
+        }
+    }
+
+    /// Returns `true` if the editor is [`Full`].
+    ///
+    /// [`Full`]: Editor::Full
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full { .. })
+    }
+
+    /// Returns `true` if the editor is [`Limited`].
+    ///
+    /// [`Limited`]: Editor::Limited
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        matches!(self, Self::Limited { .. })
+    }
+}
+
+impl Drag {
+    /// Moves the end of the drag. Constraining it makes rectangles square, and lines horizontal,
+    /// vertical or diagonal.
+    pub fn set_end(&mut self, end: [usize; 2], constrain: bool) {
+        let [start_x, start_y] = self.start.map(|x| x as isize);
+        let [dx, dy] = [end[0] as isize - start_x, end[1] as isize - start_y];
+
+        let [dx, dy] = match (constrain, self.shape) {
+            (false, _) => [dx, dy],
+            (true, Shape::Rectangle | Shape::Selection) => {
+                let size = dx.abs().max(dy.abs());
+
+                [size * dx.signum(), size * dy.signum()]
+            }
+            (true, Shape::Line) => {
+                if dx.abs() > 2 * dy.abs() {
+                    [dx, 0]
+                } else if dy.abs() > 2 * dx.abs() {
+                    [0, dy]
+                } else {
+                    let size = dx.abs().max(dy.abs());
+
+                    [size * dx.signum(), size * dy.signum()]
+                }
+            }
+        };
+
+        // Keep the end on the screen, which can make a constrained shape lose its proportions
+        self.end = [
+            (start_x + dx).clamp(0, Levels::LEVEL_WIDTH as isize - 1) as usize,
+            (start_y + dy).clamp(0, Levels::LEVEL_HEIGHT as isize - 1) as usize,
+        ];
+    }
+
+    /// The positions of the tiles the shape covers
+    #[must_use]
+    pub fn positions(&self) -> Vec<[usize; 2]> {
+        let [start_x, start_y] = self.start;
+        let [end_x, end_y] = self.end;
+
+        match self.shape {
+            Shape::Rectangle | Shape::Selection => {
+                let xs = start_x.min(end_x)..=start_x.max(end_x);
+                let ys = start_y.min(end_y)..=start_y.max(end_y);
+
+                xs.flat_map(|x| ys.clone().map(move |y| [x, y])).collect()
+            }
+            Shape::Line => {
+                // Steps along the longer axis, rounding the position along the shorter one
+                let [dx, dy] = [
+                    end_x as isize - start_x as isize,
+                    end_y as isize - start_y as isize,
+                ];
+                let steps = dx.abs().max(dy.abs());
+
+                (0..=steps)
+                    .map(|step| {
+                        let fraction = step as f32 / steps.max(1) as f32;
+
+                        [
+                            (start_x as f32 + dx as f32 * fraction).round() as usize,
+                            (start_y as f32 + dy as f32 * fraction).round() as usize,
+                        ]
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Clipboard {
+    /// Copies the selected tiles, returning whether there were any
+    pub fn copy(&mut self, levels: &Levels) -> bool {
+        let Some(selection) = self.selection else {
+            return false;
+        };
+
+        let size = [0, 1].map(|i| selection.start[i].abs_diff(selection.end[i]) + 1);
+        let tiles = selection
+            .positions()
+            .into_iter()
+            .map(|position| levels.get(position).copied().unwrap_or(Tile::Solid))
+            .collect();
+
+        self.copied = Some(CopiedTiles { size, tiles });
+
+        true
+    }
+
+    /// Starts following the mouse with the copied tiles, returning whether there are any
+    pub fn start_pasting(&mut self) -> bool {
+        self.pasting = self.copied.is_some();
+        self.pasting
+    }
+
+    /// Stops pasting, or clears the selection if not pasting. Returns whether there was anything
+    /// to cancel.
+    pub fn cancel(&mut self) -> bool {
+        if self.pasting {
+            self.pasting = false;
+            true
+        } else {
+            self.selection.take().is_some()
+        }
+    }
+
+    /// The positions on the screen the copied tiles would go if pasted with their bottom left
+    /// corner at `corner`, along with the tiles. Tiles which would go off the screen are left out.
+    #[must_use]
+    pub fn paste_positions(&self, corner: [usize; 2]) -> Vec<([usize; 2], Tile)> {
+        let Some(copied) = &self.copied else {
+            return Vec::new();
+        };
+
+        let [_, height] = copied.size;
+
+        copied
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| ([corner[0] + i / height, corner[1] + i % height], *tile))
+            .filter(|([x, y], _)| *x < Levels::LEVEL_WIDTH && *y < Levels::LEVEL_HEIGHT)
+            .collect()
+    }
+}
+
+/// Returns `true` if a gem can rest in the tile at `tile_index`, which has to be empty with a solid
+/// tile below it, not have the other gem in it, and not be close enough for the player to touch
+/// the gem there
+#[must_use]
+pub fn can_place_gem(tile_index: usize, levels: &Levels, player: &Player) -> bool {
+    if tile_index >= levels.tiles.len() || tile_index.is_multiple_of(Levels::LEVEL_HEIGHT) {
+        return false;
+    }
+
+    let resting_on_solid_tile =
+        levels.tiles[tile_index] == Tile::Empty && levels.tiles[tile_index - 1].is_solid();
+    let taken = [levels.limited_gem, levels.full_gem].contains(&Some(tile_index));
+
+    // The gem sits in the solid tile below while its editor is enabled
+    let touches_player = [false, true]
+        .into_iter()
+        .filter_map(|enabled| levels.gem_center(tile_index, enabled))
+        .any(|center| player.touches_pickup(Pickup::Gem, center));
+
+    resting_on_solid_tile && !taken && !touches_player
+}
+
+/// Returns `true` if the tile is a gem or the tile it rests on, which can't be changed
+fn is_under_gem(tile_index: usize, levels: &Levels) -> bool {
+    [levels.limited_gem, levels.full_gem]
+        .into_iter()
+        .flatten()
+        .any(|gem_index| tile_index == gem_index || tile_index == gem_index - 1)
+}
+
+fn toggle(tile: &mut Tile) {
+    *tile = tile.toggled();
+}
@@ -0,0 +1,130 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::keybinds::Action;
+use crate::player::Player;
+
+/// A readable list of inputs for scripted scenarios, such as `right 30 ticks; jump; wait 10;
+/// invert`. Each step holds its movement actions, joined with `+`, for a number of ticks, or one
+/// tick if no number is given. `wait` holds nothing, and `swap` is another name for `invert`.
+/// Steps are separated by semicolons or new lines.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputScript {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    actions: Vec<Action>,
+    ticks: usize,
+}
+
+/// The input for one tick of an [`InputScript`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScriptedInput {
+    /// The movement inputs held down, in the same order as [`Player::inputs_down`]
+    pub down: [bool; 4],
+    /// Whether this is the first tick of its step, which presses everything held down
+    pub pressed: bool,
+}
+
+impl InputScript {
+    /// The number of ticks the script lasts for
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.iter().map(|step| step.ticks).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The input for each tick of the script in order
+    pub fn inputs(&self) -> impl Iterator<Item = ScriptedInput> + '_ {
+        self.steps.iter().flat_map(|step| {
+            let mut down = [false; 4];
+
+            for action in &step.actions {
+                down[action.index()] = true;
+            }
+
+            (0..step.ticks).map(move |tick| ScriptedInput {
+                down,
+                pressed: tick == 0,
+            })
+        })
+    }
+}
+
+impl ScriptedInput {
+    /// Gives the input to the player for their next update
+    pub fn apply(self, player: &mut Player) {
+        let pressed = if self.pressed { self.down } else { [false; 4] };
+
+        player.add_input(self.down, pressed);
+    }
+}
+
+impl FromStr for InputScript {
+    type Err = ParseInputScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|step| !step.is_empty())
+            .enumerate()
+            .map(|(i, step)| {
+                let mut words = step.split_whitespace();
+
+                let actions = words
+                    .next()
+                    .unwrap()
+                    .split('+')
+                    .filter(|name| *name != "wait")
+                    .map(|name| {
+                        let name = if name == "swap" { "invert" } else { name };
+
+                        [Action::Jump, Action::Left, Action::Invert, Action::Right]
+                            .into_iter()
+                            .find(|action| action.name() == name)
+                            .ok_or(ParseInputScriptError::UnknownAction(i))
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                let ticks = match (words.next(), words.next(), words.next()) {
+                    (None, None, None) => 1,
+                    (Some(ticks), None | Some("tick" | "ticks"), None) => ticks
+                        .parse()
+                        .map_err(|_| ParseInputScriptError::InvalidTicks(i))?,
+                    _ => return Err(ParseInputScriptError::InvalidTicks(i)),
+                };
+
+                Ok(Step { actions, ticks })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { steps })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ParseInputScriptError {
+    UnknownAction(usize),
+    InvalidTicks(usize),
+}
+
+impl Display for ParseInputScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseInputScriptError::UnknownAction(step) => {
+                write!(f, "unknown action in step {}", step + 1)
+            }
+            ParseInputScriptError::InvalidTicks(step) => {
+                write!(f, "expected a number of ticks in step {}", step + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseInputScriptError {}
@@ -0,0 +1,686 @@
+use std::{env, fmt::Display, io, str::FromStr};
+
+use macroquad::input::{self, KeyCode};
+
+use crate::storage::TrackedFile;
+
+pub const KEYBINDS_FILE_NAME: &str = "keybinds.txt";
+
+/// Something the player can do with a key. The first four are the player's movement, in the same
+/// order as [`Player::inputs_down`](crate::player::Player::inputs_down).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Jump,
+    Left,
+    Invert,
+    Right,
+    Respawn,
+    Fullscreen,
+    DebugOverlay,
+    /// Writes the levels straight away rather than waiting for the next autosave
+    SaveLevels,
+    Copy,
+    Paste,
+    /// Held while clicking in the full editor to draw a line rather than a rectangle
+    DrawLine,
+    /// Held while clicking in the full editor to select tiles to copy
+    Select,
+    /// Held while dragging in the full editor to keep shapes square or lines straight
+    Constrain,
+    /// Opens the full editor's list of levels
+    LevelList,
+    /// Held while clicking in the full editor to place, move or remove the limited gem, or the
+    /// full gem with the right mouse button
+    PlaceGems,
+    /// Moves the focus up the level list
+    ListUp,
+    /// Moves the focus down the level list
+    ListDown,
+    /// Held while moving the focus in the level list to move the focused level along with it
+    MoveLevel,
+    DuplicateLevel,
+    RenameLevel,
+}
+
+/// Where an [`Action`] is used, which namespaces it in the keybinds file. Every context can be in
+/// use at once while editing, so bindings conflict across contexts as well as within them, other
+/// than the level list's actions, which only work while the rest of the game is paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Context {
+    Gameplay,
+    Editor,
+    /// Actions which work anywhere, even in menus
+    Global,
+}
+
+/// A built in set of keys for the gameplay actions, to suit different keyboards and hands
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// W, A, S and D, along with the arrow keys
+    Qwerty,
+    /// Z, Q, S and D, which are where W, A, S and D are on a QWERTY keyboard
+    Azerty,
+    Arrows,
+    /// I, J, K and L, leaving the left hand free for the mouse
+    LeftHanded,
+}
+
+/// A key, along with any modifier keys which have to be held when it's pressed, such as
+/// `Ctrl+C`. Bindings with fewer modifiers still work while more are held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub modifiers: Modifiers,
+    pub key: KeyCode,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+}
+
+/// The keys which can be bound, which are named in the keybinds file by their [`Debug`] names
+const BINDABLE_KEYS: [KeyCode; 58] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+
+impl Action {
+    pub const ALL: [Action; 20] = [
+        Action::Jump,
+        Action::Left,
+        Action::Invert,
+        Action::Right,
+        Action::Respawn,
+        Action::Fullscreen,
+        Action::DebugOverlay,
+        Action::SaveLevels,
+        Action::Copy,
+        Action::Paste,
+        Action::DrawLine,
+        Action::Select,
+        Action::Constrain,
+        Action::LevelList,
+        Action::PlaceGems,
+        Action::ListUp,
+        Action::ListDown,
+        Action::MoveLevel,
+        Action::DuplicateLevel,
+        Action::RenameLevel,
+    ];
+
+    /// The name of the action in the keybinds file
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Jump => "jump",
+            Action::Left => "left",
+            Action::Invert => "invert",
+            Action::Right => "right",
+            Action::Respawn => "respawn",
+            Action::Fullscreen => "fullscreen",
+            Action::DebugOverlay => "debug_overlay",
+            Action::SaveLevels => "save_levels",
+            Action::Copy => "copy",
+            Action::Paste => "paste",
+            Action::DrawLine => "draw_line",
+            Action::Select => "select",
+            Action::Constrain => "constrain",
+            Action::LevelList => "level_list",
+            Action::PlaceGems => "place_gems",
+            Action::ListUp => "list_up",
+            Action::ListDown => "list_down",
+            Action::MoveLevel => "move_level",
+            Action::DuplicateLevel => "duplicate_level",
+            Action::RenameLevel => "rename_level",
+        }
+    }
+
+    #[must_use]
+    pub fn context(self) -> Context {
+        match self {
+            Action::Jump | Action::Left | Action::Invert | Action::Right | Action::Respawn => {
+                Context::Gameplay
+            }
+            Action::SaveLevels
+            | Action::Copy
+            | Action::Paste
+            | Action::DrawLine
+            | Action::Select
+            | Action::Constrain
+            | Action::LevelList
+            | Action::PlaceGems
+            | Action::ListUp
+            | Action::ListDown
+            | Action::MoveLevel
+            | Action::DuplicateLevel
+            | Action::RenameLevel => Context::Editor,
+            Action::Fullscreen | Action::DebugOverlay => Context::Global,
+        }
+    }
+
+    /// Returns `true` if the action is only used in the full editor's level list, which pauses
+    /// everything other than the global actions while it's open
+    #[must_use]
+    pub fn is_in_level_list(self) -> bool {
+        matches!(
+            self,
+            Action::ListUp
+                | Action::ListDown
+                | Action::MoveLevel
+                | Action::DuplicateLevel
+                | Action::RenameLevel
+        )
+    }
+
+    /// Returns `true` if the actions can be used at the same time, so their bindings mustn't
+    /// overlap
+    fn is_used_with(self, other: Action) -> bool {
+        self.is_in_level_list() == other.is_in_level_list()
+            || self.context() == Context::Global
+            || other.context() == Context::Global
+    }
+
+    pub(crate) fn index(self) -> usize {
+        Self::ALL.iter().position(|action| *action == self).unwrap()
+    }
+}
+
+impl Context {
+    pub const ALL: [Context; 3] = [Context::Gameplay, Context::Editor, Context::Global];
+
+    /// The namespace of the context's actions in the keybinds file
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Context::Gameplay => "gameplay",
+            Context::Editor => "editor",
+            Context::Global => "global",
+        }
+    }
+
+    /// The actions used in the context, in the order of [`Action::ALL`]
+    pub fn actions(self) -> impl Iterator<Item = Action> {
+        Action::ALL
+            .into_iter()
+            .filter(move |action| action.context() == self)
+    }
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Qwerty,
+        Preset::Azerty,
+        Preset::Arrows,
+        Preset::LeftHanded,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Qwerty => "qwerty",
+            Preset::Azerty => "azerty",
+            Preset::Arrows => "arrows",
+            Preset::LeftHanded => "left_handed",
+        }
+    }
+
+    /// The keys the preset binds to a gameplay action
+    #[must_use]
+    pub fn keys(self, action: Action) -> &'static [KeyCode] {
+        use KeyCode::*;
+
+        match (self, action) {
+            (Preset::Qwerty, Action::Jump) => &[W, Up, Space],
+            (Preset::Qwerty, Action::Left) => &[A, Left],
+            (Preset::Azerty, Action::Jump) => &[Z, Up, Space],
+            (Preset::Azerty, Action::Left) => &[Q, Left],
+            (Preset::Qwerty | Preset::Azerty, Action::Invert) => &[S, Down],
+            (Preset::Qwerty | Preset::Azerty, Action::Right) => &[D, Right],
+            (Preset::Arrows, Action::Jump) => &[Up],
+            (Preset::Arrows, Action::Left) => &[Left],
+            (Preset::Arrows, Action::Invert) => &[Down],
+            (Preset::Arrows, Action::Right) => &[Right],
+            (Preset::LeftHanded, Action::Jump) => &[I],
+            (Preset::LeftHanded, Action::Left) => &[J],
+            (Preset::LeftHanded, Action::Invert) => &[K],
+            (Preset::LeftHanded, Action::Right) => &[L],
+            (Preset::LeftHanded, Action::Respawn) => &[P],
+            (_, Action::Respawn) => &[R],
+            _ => &[],
+        }
+    }
+
+    /// The preset which suits the keyboards used with a locale such as `fr_FR.UTF-8`, if it isn't
+    /// the QWERTY one. Most layouts keep W, A, S and D where QWERTY has them, so only AZERTY is
+    /// suggested.
+    #[must_use]
+    pub fn for_locale(locale: &str) -> Option<Self> {
+        let locale = locale.split(['.', '@']).next()?;
+        let (language, region) = locale.split_once(['_', '-'])?;
+
+        match (language, region) {
+            ("fr", "FR" | "BE" | "MC" | "LU") | ("nl", "BE") | ("br" | "oc", "FR") => {
+                Some(Preset::Azerty)
+            }
+            _ => None,
+        }
+    }
+
+    /// The preset suggested by the system's locale. Macroquad can't say which keyboard layout is
+    /// in use, so this is the best guess there is.
+    #[must_use]
+    pub fn suggested() -> Option<Self> {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|variable| env::var(variable).ok().filter(|value| !value.is_empty()))
+            .and_then(|locale| Self::for_locale(&locale))
+    }
+}
+
+impl Binding {
+    /// Returns `true` if pressing one of the bindings would also trigger the other
+    #[must_use]
+    pub fn overlaps(self, other: Binding) -> bool {
+        self.key == other.key
+            && (self.modifiers.contains(other.modifiers)
+                || other.modifiers.contains(self.modifiers))
+    }
+
+    #[must_use]
+    pub fn is_down(self) -> bool {
+        input::is_key_down(self.key) && Modifiers::held().contains(self.modifiers)
+    }
+
+    #[must_use]
+    pub fn is_pressed(self) -> bool {
+        input::is_key_pressed(self.key) && Modifiers::held().contains(self.modifiers)
+    }
+}
+
+impl From<KeyCode> for Binding {
+    fn from(key: KeyCode) -> Self {
+        Self {
+            modifiers: Modifiers::default(),
+            key,
+        }
+    }
+}
+
+impl Modifiers {
+    pub const CONTROL: Modifiers = Modifiers {
+        shift: false,
+        control: true,
+        alt: false,
+    };
+
+    /// The modifier keys being held down
+    #[must_use]
+    pub fn held() -> Self {
+        let either = |left, right| input::is_key_down(left) || input::is_key_down(right);
+
+        Self {
+            shift: either(KeyCode::LeftShift, KeyCode::RightShift),
+            control: either(KeyCode::LeftControl, KeyCode::RightControl),
+            alt: either(KeyCode::LeftAlt, KeyCode::RightAlt),
+        }
+    }
+
+    /// Returns `true` if the key is one of the modifier keys, which are bound on their own rather
+    /// than as part of a chord
+    #[must_use]
+    pub fn is_modifier_key(key: KeyCode) -> bool {
+        matches!(
+            key,
+            KeyCode::LeftShift
+                | KeyCode::RightShift
+                | KeyCode::LeftControl
+                | KeyCode::RightControl
+                | KeyCode::LeftAlt
+                | KeyCode::RightAlt
+        )
+    }
+
+    /// Returns `true` if every modifier in `other` is also in `self`
+    #[must_use]
+    pub fn contains(self, other: Modifiers) -> bool {
+        (self.shift || !other.shift) && (self.control || !other.control) && (self.alt || !other.alt)
+    }
+}
+
+/// Which keys do each [`Action`], stored in the data directory as one line per action, namespaced
+/// by its [`Context`], such as `gameplay.jump = W Up Space` or `editor.copy = Ctrl+C`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keybinds {
+    bindings: [Vec<Binding>; Action::ALL.len()],
+}
+
+impl Keybinds {
+    /// Reads the keybinds file, using the default keybinds for any actions it leaves out. If there
+    /// isn't a file yet, the default keybinds are used with the [`Preset::suggested`] preset.
+    pub fn load(file: &mut TrackedFile) -> io::Result<Self> {
+        match file.read() {
+            Ok(text) => text
+                .parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                let mut keybinds = Self::default();
+
+                if let Some(preset) = Preset::suggested() {
+                    keybinds.apply_preset(preset);
+                }
+
+                Ok(keybinds)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn save(&self, file: &mut TrackedFile) -> io::Result<()> {
+        file.write(self.to_string()).map(|_| ())
+    }
+
+    #[must_use]
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        &self.bindings[action.index()]
+    }
+
+    /// Binds the key or chord to the action if it isn't already, or unbinds it if it is. Returns
+    /// `false` if the key can't be bound.
+    pub fn toggle_binding(&mut self, action: Action, binding: impl Into<Binding>) -> bool {
+        let binding = binding.into();
+
+        if !BINDABLE_KEYS.contains(&binding.key) {
+            return false;
+        }
+
+        let bindings = &mut self.bindings[action.index()];
+
+        if let Some(index) = bindings.iter().position(|bound| *bound == binding) {
+            bindings.remove(index);
+        } else {
+            bindings.push(binding);
+        }
+
+        true
+    }
+
+    /// Binds the gameplay actions to the preset's keys, leaving the others alone
+    pub fn apply_preset(&mut self, preset: Preset) {
+        for action in Context::Gameplay.actions() {
+            self.bindings[action.index()] = preset
+                .keys(action)
+                .iter()
+                .copied()
+                .map(Binding::from)
+                .collect();
+        }
+    }
+
+    /// The preset the gameplay actions are bound with, if they haven't been changed from one
+    #[must_use]
+    pub fn preset(&self) -> Option<Preset> {
+        Preset::ALL.into_iter().find(|preset| {
+            Context::Gameplay.actions().all(|action| {
+                self.bindings(action).iter().copied().eq(preset
+                    .keys(action)
+                    .iter()
+                    .copied()
+                    .map(Binding::from))
+            })
+        })
+    }
+
+    /// The other actions with a binding which would be triggered along with one of this action's
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|other| *other != action && action.is_used_with(*other))
+            .filter(|other| {
+                self.bindings(action).iter().any(|binding| {
+                    self.bindings(*other)
+                        .iter()
+                        .any(|other_binding| binding.overlaps(*other_binding))
+                })
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn is_down(&self, action: Action) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_down())
+    }
+
+    #[must_use]
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_pressed())
+    }
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        let keys = |keys: &[KeyCode]| keys.iter().copied().map(Binding::from).collect();
+        let control = |key| {
+            vec![Binding {
+                modifiers: Modifiers::CONTROL,
+                key,
+            }]
+        };
+
+        Self {
+            bindings: Action::ALL.map(|action| match action {
+                Action::Jump | Action::Left | Action::Invert | Action::Right | Action::Respawn => {
+                    keys(Preset::Qwerty.keys(action))
+                }
+                Action::Fullscreen => keys(&[KeyCode::F11]),
+                Action::DebugOverlay => keys(&[KeyCode::F2]),
+                Action::SaveLevels => keys(&[KeyCode::F5]),
+                Action::Copy => control(KeyCode::C),
+                Action::Paste => control(KeyCode::V),
+                Action::DrawLine => keys(&[KeyCode::LeftControl, KeyCode::RightControl]),
+                Action::Select => keys(&[KeyCode::LeftAlt, KeyCode::RightAlt]),
+                Action::Constrain => keys(&[KeyCode::LeftShift, KeyCode::RightShift]),
+                Action::LevelList => keys(&[KeyCode::Tab]),
+                Action::PlaceGems => keys(&[KeyCode::G]),
+                Action::ListUp => keys(&[KeyCode::Up]),
+                Action::ListDown => keys(&[KeyCode::Down]),
+                Action::MoveLevel => keys(&[KeyCode::LeftShift, KeyCode::RightShift]),
+                Action::DuplicateLevel => keys(&[KeyCode::D]),
+                Action::RenameLevel => keys(&[KeyCode::Enter]),
+            }),
+        }
+    }
+}
+
+/// Writes the name of a key as it appears in the keybinds file
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Modifiers {
+            shift,
+            control,
+            alt,
+        } = self.modifiers;
+
+        for (held, name) in [(control, "Ctrl"), (alt, "Alt"), (shift, "Shift")] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+impl FromStr for Binding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifier_names, key_name_part) = s.rsplit_once('+').unwrap_or(("", s));
+
+        let mut modifiers = Modifiers::default();
+
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            let modifier = match name {
+                "Ctrl" => &mut modifiers.control,
+                "Alt" => &mut modifiers.alt,
+                "Shift" => &mut modifiers.shift,
+                _ => return Err(()),
+            };
+
+            *modifier = true;
+        }
+
+        let key = BINDABLE_KEYS
+            .into_iter()
+            .find(|key| key_name(*key) == key_name_part)
+            .ok_or(())?;
+
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl Display for Keybinds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for action in Action::ALL {
+            write!(f, "{}.{} =", action.context().name(), action.name())?;
+
+            for binding in self.bindings(action) {
+                write!(f, " {binding}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Keybinds {
+    type Err = ParseKeybindsError;
+
+    /// Parses the keybinds file. Actions can be written without their context, as they were
+    /// before contexts were added.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keybinds = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (name, bindings) = line
+                .split_once('=')
+                .ok_or(ParseKeybindsError::InvalidLine(i))?;
+
+            let (context_name, action_name) = match name.trim().split_once('.') {
+                Some((context_name, action_name)) => (Some(context_name), action_name),
+                None => (None, name.trim()),
+            };
+
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| {
+                    action.name() == action_name
+                        && context_name.is_none_or(|name| name == action.context().name())
+                })
+                .ok_or(ParseKeybindsError::InvalidLine(i))?;
+
+            keybinds.bindings[action.index()] = bindings
+                .split_whitespace()
+                .map(|binding| {
+                    binding
+                        .parse()
+                        .map_err(|()| ParseKeybindsError::UnknownKey(i))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(keybinds)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ParseKeybindsError {
+    InvalidLine(usize),
+    UnknownKey(usize),
+}
+
+impl Display for ParseKeybindsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseKeybindsError::InvalidLine(line) => {
+                write!(f, "expected `action = keys` on line {}", line + 1)
+            }
+            ParseKeybindsError::UnknownKey(line) => {
+                write!(f, "unknown key on line {}", line + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseKeybindsError {}
@@ -1,50 +1,163 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt::Display,
+    hash::{Hash, Hasher},
     ops::{Index, IndexMut},
     str::FromStr,
 };
 
+use crate::physics::{PhysicsConfig, PhysicsOverrides};
+use crate::pickup::Pickup;
+use crate::tile::Tile;
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SerializedLevels"))]
 pub struct Levels {
-    pub tiles: Vec<bool>,
+    pub tiles: Vec<Tile>,
     pub num_levels: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub level_index: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub x_offset: usize,
     pub limited_gem: Option<usize>,
     pub full_gem: Option<usize>,
-    pub animation: f32,
+    pub metadata: Vec<LevelMetadata>,
+    /// The levels whose switch region is flipped. The flipped tiles are written back the way they
+    /// were, so switches only last for the session.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub switched: BTreeSet<usize>,
+}
+
+/// A rearrangement of the levels, which anything kept by level index or tile index has to follow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelChange {
+    /// A new level was inserted at an index, shifting the levels from there along by one
+    Inserted(usize),
+    /// A level was removed, shifting the levels after it back by one
+    Removed(usize),
+    /// A level was moved to index `to`, shifting the levels in between along by one
+    Moved { from: usize, to: usize },
+    /// A copy of a level was inserted after it
+    Duplicated(usize),
+}
+
+impl LevelChange {
+    /// The index a level ends up at after the change, or `None` if it was removed
+    #[must_use]
+    pub fn level_index(self, level_index: usize) -> Option<usize> {
+        let new_index = match self {
+            LevelChange::Inserted(index) if level_index >= index => level_index + 1,
+            LevelChange::Removed(index) if level_index == index => return None,
+            LevelChange::Removed(index) if level_index > index => level_index - 1,
+            LevelChange::Moved { from, to } if level_index == from => to,
+            LevelChange::Moved { from, to } if from < level_index && level_index <= to => {
+                level_index - 1
+            }
+            LevelChange::Moved { from, to } if to <= level_index && level_index < from => {
+                level_index + 1
+            }
+            LevelChange::Duplicated(index) if level_index > index => level_index + 1,
+            _ => level_index,
+        };
+
+        Some(new_index)
+    }
+
+    /// The index a tile ends up at after the change, which stays in the same place in its level,
+    /// or `None` if its level was removed
+    #[must_use]
+    pub fn tile_index(self, tile_index: usize) -> Option<usize> {
+        let level_index = self.level_index(tile_index / LEVEL_TILES)?;
+
+        Some(level_index * LEVEL_TILES + tile_index % LEVEL_TILES)
+    }
+}
+
+/// Information about a level which isn't part of its tiles, stored in the header of the levels
+/// file
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LevelMetadata {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    /// The time in seconds a good run of the level should take
+    pub par_time: Option<f32>,
+    pub physics: PhysicsOverrides,
+    /// Opposite corners of the tiles which switches in the level flip, as positions within the
+    /// level
+    pub switch_region: Option<[[usize; 2]; 2]>,
+}
+
+impl LevelMetadata {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `true` if the metadata can be used in a level, the same as the levels file checks
+    /// when parsing it
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.physics.is_valid() && self.switch_region.is_none_or(is_valid_switch_region)
+    }
+}
+
+/// Returns `true` if both corners of a switch region are inside a level
+fn is_valid_switch_region(region: [[usize; 2]; 2]) -> bool {
+    region
+        .iter()
+        .all(|[x, y]| *x < Levels::LEVEL_WIDTH - 1 && *y < Levels::LEVEL_HEIGHT)
 }
 
 impl Levels {
     pub const LEVEL_WIDTH: usize = 15;
     pub const LEVEL_HEIGHT: usize = 11;
 
+    /// The version of the levels file format written by [`Display`]. Files without a header are
+    /// treated as version 1, version 3 added tiles other than solid and empty ones, version 4
+    /// added switches, version 5 added coins, version 6 added double jumps, and version 7 added
+    /// curses, which earlier versions of the game could write without saying so.
+    pub const FORMAT_VERSION: u32 = 7;
+
     pub fn new() -> Self {
         Self {
-            tiles: vec![false; (Self::LEVEL_WIDTH - 1) * Self::LEVEL_HEIGHT],
+            tiles: vec![Tile::Empty; (Self::LEVEL_WIDTH - 1) * Self::LEVEL_HEIGHT],
             num_levels: 1,
             level_index: 0,
             x_offset: 0,
             limited_gem: None,
             full_gem: None,
-            animation: 0.0,
+            metadata: vec![LevelMetadata::default()],
+            switched: BTreeSet::new(),
         }
     }
 
-    pub fn update_animation_counter(&mut self) {
-        self.animation += macroquad::time::get_frame_time();
-        self.animation %= 24.0;
-    }
-
-    pub fn get_from_position(&self, position: [f32; 2]) -> Option<bool> {
+    pub fn get_from_position(&self, position: [f32; 2]) -> Option<Tile> {
         match self.index_of_position(position) {
             Ok(index) => Some(*self.get(index).unwrap()),
-            Err([None, Some(IndexingError::TooBig)]) => Some(false),
-            Err([None, Some(IndexingError::TooSmall)]) => Some(true),
+            Err([None, Some(IndexingError::TooBig)]) => Some(Tile::Empty),
+            Err([None, Some(IndexingError::TooSmall)]) => Some(Tile::Solid),
             _ => None,
         }
     }
 
+    /// The physics of the current level, with its overrides applied
+    pub fn physics(&self) -> PhysicsConfig {
+        PhysicsConfig::DEFAULT.with_overrides(&self.metadata[self.level_index].physics)
+    }
+
+    /// The center of a gem, which is where it's drawn and picked up from. Gems float in their
+    /// tile, and sink into the tile below while the editor they give is enabled.
+    #[must_use]
+    pub fn gem_center(&self, gem_index: usize, enabled: bool) -> Option<[f32; 2]> {
+        let [x, y] = self.position_of_tile_index(gem_index)?;
+        let offset = if enabled { -0.5 } else { 0.5 };
+
+        Some([x + 0.5, y + offset])
+    }
+
     pub fn position_of_tile_index(&self, tile_index: usize) -> Option<[f32; 2]> {
         let x = tile_index / Self::LEVEL_HEIGHT;
         let y = tile_index % Self::LEVEL_HEIGHT;
@@ -86,13 +199,13 @@ impl Levels {
         }
     }
 
-    pub fn get(&self, index: [usize; 2]) -> Option<&bool> {
+    pub fn get(&self, index: [usize; 2]) -> Option<&Tile> {
         let tile_index = self.index_of(index)?;
 
         Some(&self.tiles[tile_index])
     }
 
-    pub fn get_mut(&mut self, index: [usize; 2]) -> Option<&mut bool> {
+    pub fn get_mut(&mut self, index: [usize; 2]) -> Option<&mut Tile> {
         let tile_index = self.index_of(index)?;
 
         Some(&mut self.tiles[tile_index])
@@ -133,14 +246,17 @@ impl Levels {
         self.update_level_offset();
     }
 
-    pub fn insert_level(&mut self, index: usize) {
+    /// Inserts a new level at an index, with a floor along its bottom. The current level and the
+    /// gems stay with the levels they're in. Returns the change, which anything else kept by level
+    /// or tile index has to follow.
+    pub fn insert_level(&mut self, index: usize) -> LevelChange {
+        self.reset_switches();
+
         self.num_levels += 1;
 
         assert!(index < self.num_levels);
 
-        if self.level_index >= index {
-            self.next_level();
-        }
+        self.metadata.insert(index, LevelMetadata::default());
 
         let mut offset = Self::offset_of_level(index);
 
@@ -148,30 +264,156 @@ impl Levels {
 
         for _ in 0..(Self::LEVEL_WIDTH - 1) {
             for _ in 0..5 {
-                self.tiles.insert(offset, true);
+                self.tiles.insert(offset, Tile::Solid);
                 offset += 1;
             }
 
             for _ in 0..Self::LEVEL_HEIGHT - 5 {
-                self.tiles.insert(offset, false);
+                self.tiles.insert(offset, Tile::Empty);
                 offset += 1;
             }
         }
+
+        let change = LevelChange::Inserted(index);
+        self.remap_levels(change);
+
+        change
     }
 
-    pub fn remove_level(&mut self, index: usize) {
-        assert!(index < self.num_levels);
+    /// Removes a level, along with any gem in it. The current level and the other gem stay with
+    /// the levels they're in, and if the current level is removed, the level after it, or the one
+    /// before it if it was the last, becomes the current level. Returns the change, which anything
+    /// else kept by level or tile index has to follow.
+    pub fn remove_level(&mut self, index: usize) -> LevelChange {
+        assert!(index < self.num_levels && self.num_levels > 1);
+
+        self.reset_switches();
 
         self.num_levels -= 1;
 
-        if self.level_index > index {
-            self.previous_level();
-        }
+        self.metadata.remove(index);
 
         let offset = Self::offset_of_level(index);
+        self.tiles.drain(offset..offset + LEVEL_TILES);
+
+        let change = LevelChange::Removed(index);
+        self.remap_levels(change);
 
-        for _ in 0..(Self::LEVEL_WIDTH - 1) * Self::LEVEL_HEIGHT {
-            self.tiles.remove(offset);
+        change
+    }
+
+    /// Moves a level so that it ends up at index `to`, shifting the levels in between along by
+    /// one. The current level and the gems stay with the levels they're in. Returns the change,
+    /// which anything else kept by level or tile index has to follow.
+    pub fn move_level(&mut self, from: usize, to: usize) -> LevelChange {
+        assert!(from < self.num_levels && to < self.num_levels);
+
+        self.reset_switches();
+
+        let level = self
+            .tiles
+            .drain(Self::offset_of_level(from)..Self::offset_of_level(from + 1))
+            .collect::<Vec<_>>();
+        let offset = Self::offset_of_level(to);
+        self.tiles.splice(offset..offset, level);
+
+        let metadata = self.metadata.remove(from);
+        self.metadata.insert(to, metadata);
+
+        let change = LevelChange::Moved { from, to };
+        self.remap_levels(change);
+
+        change
+    }
+
+    /// Inserts a copy of a level after it, other than its gems, which there can only be one of.
+    /// The copy's name, if it has one, says that it's a copy. Returns the change, which anything
+    /// else kept by level or tile index has to follow.
+    pub fn duplicate_level(&mut self, index: usize) -> LevelChange {
+        assert!(index < self.num_levels);
+
+        self.reset_switches();
+
+        let level =
+            self.tiles[Self::offset_of_level(index)..Self::offset_of_level(index + 1)].to_vec();
+        let offset = Self::offset_of_level(index + 1);
+        self.tiles.splice(offset..offset, level);
+
+        let mut metadata = self.metadata[index].clone();
+        metadata.name = metadata.name.map(|name| format!("{name} copy"));
+        self.metadata.insert(index + 1, metadata);
+
+        self.num_levels += 1;
+
+        let change = LevelChange::Duplicated(index);
+        self.remap_levels(change);
+
+        change
+    }
+
+    /// Moves the current level and the gems along with their levels, after the levels have been
+    /// rearranged
+    fn remap_levels(&mut self, change: LevelChange) {
+        for gem_index in [&mut self.limited_gem, &mut self.full_gem] {
+            *gem_index = gem_index.and_then(|gem_index| change.tile_index(gem_index));
+        }
+
+        self.level_index = change
+            .level_index(self.level_index)
+            .unwrap_or(self.level_index.min(self.num_levels - 1));
+        self.update_level_offset();
+    }
+
+    /// The indices of the tiles a level's switches flip, which are the plain tiles in its switch
+    /// region other than gems and the tiles they rest on
+    #[must_use]
+    pub fn switch_tiles(&self, level_index: usize) -> Vec<usize> {
+        let Some([start, end]) = self.metadata[level_index].switch_region else {
+            return Vec::new();
+        };
+
+        let xs = start[0].min(end[0])..=start[0].max(end[0]);
+        let ys = start[1].min(end[1])..=start[1].max(end[1]);
+
+        xs.flat_map(|x| ys.clone().map(move |y| [x, y]))
+            .map(|[x, y]| Self::offset_of_level(level_index) + x * Self::LEVEL_HEIGHT + y)
+            .filter(|tile_index| {
+                let under_gem = [self.limited_gem, self.full_gem]
+                    .into_iter()
+                    .flatten()
+                    .any(|gem_index| *tile_index == gem_index || *tile_index + 1 == gem_index);
+
+                self.tiles[*tile_index].is_plain() && !under_gem
+            })
+            .collect()
+    }
+
+    /// The indices of the coins in a level
+    pub fn coins(&self, level_index: usize) -> impl Iterator<Item = usize> {
+        let start = Self::offset_of_level(level_index);
+
+        (start..start + LEVEL_TILES).filter(|tile_index| self.tiles[*tile_index] == Tile::Coin)
+    }
+
+    /// Flips a level's switch region, or flips it back if it's already flipped
+    pub fn flip_switch(&mut self, level_index: usize) {
+        if self.metadata[level_index].switch_region.is_none() {
+            return;
+        }
+
+        for tile_index in self.switch_tiles(level_index) {
+            self.tiles[tile_index] = self.tiles[tile_index].toggled();
+        }
+
+        if !self.switched.remove(&level_index) {
+            self.switched.insert(level_index);
+        }
+    }
+
+    /// Flips back every switch region which is flipped
+    pub fn reset_switches(&mut self) {
+        for level_index in self.switched.clone() {
+            self.flip_switch(level_index);
         }
     }
 
@@ -184,6 +426,114 @@ impl Levels {
     }
 }
 
+/// Hashes the state of the levels which can change during play
+impl Hash for Levels {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tiles.hash(state);
+        self.num_levels.hash(state);
+        self.level_index.hash(state);
+        self.x_offset.hash(state);
+        self.limited_gem.hash(state);
+        self.full_gem.hash(state);
+        self.switched.hash(state);
+    }
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Levels {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_ron(&self) -> ron::Result<String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+}
+
+/// The serialized form of [`Levels`], which is checked for the same mistakes the text format
+/// would reject before being converted
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerializedLevels {
+    tiles: Vec<Tile>,
+    num_levels: usize,
+    limited_gem: Option<usize>,
+    full_gem: Option<usize>,
+    #[serde(default)]
+    metadata: Vec<LevelMetadata>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerializedLevels> for Levels {
+    type Error = ParseLevelError;
+
+    fn try_from(levels: SerializedLevels) -> Result<Self, Self::Error> {
+        let SerializedLevels {
+            tiles,
+            num_levels,
+            limited_gem,
+            full_gem,
+            mut metadata,
+        } = levels;
+
+        if num_levels == 0 || tiles.len() != num_levels * LEVEL_TILES {
+            return Err(ParseLevelError::InvalidWidth);
+        }
+
+        for (gem, character) in [(limited_gem, 'e'), (full_gem, 'E')] {
+            let Some(gem_index) = gem else {
+                continue;
+            };
+
+            if gem_index >= tiles.len() || tiles[gem_index] != Tile::Empty {
+                return Err(ParseLevelError::InvalidGemPosition(character));
+            }
+
+            if gem_index == 0 || !tiles[gem_index - 1].is_solid() {
+                return Err(ParseLevelError::InvalidTileBelowGem);
+            }
+        }
+
+        if metadata.len() > num_levels {
+            return Err(ParseLevelError::MetadataForMissingLevel(metadata.len()));
+        }
+
+        if let Some(level_index) = metadata.iter().position(|metadata| !metadata.is_valid()) {
+            return Err(ParseLevelError::InvalidMetadata(level_index + 1));
+        }
+
+        metadata.resize_with(num_levels, LevelMetadata::default);
+
+        Ok(Self {
+            tiles,
+            num_levels,
+            level_index: 0,
+            x_offset: 0,
+            limited_gem,
+            full_gem,
+            metadata,
+            switched: BTreeSet::new(),
+        })
+    }
+}
+
+/// The number of tiles in each level
+const LEVEL_TILES: usize = (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT;
+
 #[derive(Clone, Copy, Debug)]
 pub enum IndexingError {
     TooBig,
@@ -191,7 +541,7 @@ pub enum IndexingError {
 }
 
 impl Index<[usize; 2]> for Levels {
-    type Output = bool;
+    type Output = Tile;
 
     fn index(&self, index: [usize; 2]) -> &Self::Output {
         self.get(index).unwrap()
@@ -206,37 +556,87 @@ impl IndexMut<[usize; 2]> for Levels {
 
 impl Display for Levels {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#version {}", Self::FORMAT_VERSION)?;
+
+        for (i, metadata) in self.metadata.iter().enumerate() {
+            if metadata.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "#level {}", i + 1)?;
+
+            if let Some(name) = &metadata.name {
+                writeln!(f, "#name {name}")?;
+            }
+
+            if let Some(author) = &metadata.author {
+                writeln!(f, "#author {author}")?;
+            }
+
+            if let Some(par_time) = metadata.par_time {
+                writeln!(f, "#par_time {par_time}")?;
+            }
+
+            let physics = &metadata.physics;
+
+            for (key, scale) in [
+                ("gravity_scale", physics.gravity_scale),
+                ("friction_scale", physics.friction_scale),
+                ("jump_scale", physics.jump_scale),
+            ] {
+                if let Some(scale) = scale {
+                    writeln!(f, "#{key} {scale}")?;
+                }
+            }
+
+            for (pickup, radius) in Pickup::ALL.into_iter().zip(physics.pickup_radii) {
+                if let Some(radius) = radius {
+                    writeln!(f, "#{}_radius {radius}", pickup.name())?;
+                }
+            }
+
+            if let Some([start, end]) = metadata.switch_region {
+                writeln!(
+                    f,
+                    "#switch_region {} {} {} {}",
+                    start[0], start[1], end[0], end[1]
+                )?;
+            }
+        }
+
+        // Flipped switch regions are written the way they were before being flipped
+        let mut flipped = vec![false; self.tiles.len()];
+
+        for level_index in &self.switched {
+            for tile_index in self.switch_tiles(*level_index) {
+                flipped[tile_index] = true;
+            }
+        }
+
         for y in (0..Self::LEVEL_HEIGHT).rev() {
             for x in 0..(Self::LEVEL_WIDTH - 1) * self.num_levels {
                 let tile_index = x * Self::LEVEL_HEIGHT + y;
 
-                if let Some(gem_index) = self.limited_gem {
-                    if tile_index == gem_index {
-                        write!(f, "e")?;
-                        continue;
-                    }
+                if self.limited_gem == Some(tile_index) {
+                    write!(f, "e")?;
+                    continue;
                 }
 
-                if let Some(gem_index) = self.full_gem {
-                    if tile_index == gem_index {
-                        write!(f, "E")?;
-                        continue;
-                    }
+                if self.full_gem == Some(tile_index) {
+                    write!(f, "E")?;
+                    continue;
                 }
 
-                let tile = self.tiles[x * Self::LEVEL_HEIGHT + y];
+                let tile = if flipped[tile_index] {
+                    self.tiles[tile_index].toggled()
+                } else {
+                    self.tiles[tile_index]
+                };
 
-                write!(
-                    f,
-                    "{}",
-                    match tile {
-                        true => 'x',
-                        false => ' ',
-                    }
-                )?;
+                write!(f, "{}", tile.character())?;
             }
 
-            write!(f, "|\n")?;
+            writeln!(f, "|")?;
         }
 
         Ok(())
@@ -247,104 +647,360 @@ impl FromStr for Levels {
     type Err = ParseLevelError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tiles = Vec::new();
+        parse_levels(s, &mut TextPosition { line: 1, column: 1 })
+    }
+}
 
-        let mut limited_gem = None;
-        let mut full_gem = None;
+impl Levels {
+    /// Parses a levels file like [`FromStr`], also giving where in the text the error was found if
+    /// it isn't valid
+    pub fn parse_located(s: &str) -> Result<Self, LocatedParseLevelError> {
+        let mut position = TextPosition { line: 1, column: 1 };
+
+        parse_levels(s, &mut position).map_err(|error| LocatedParseLevelError {
+            error,
+            position,
+            line: s.lines().nth(position.line - 1).unwrap_or("").to_owned(),
+        })
+    }
 
-        let mut lines = s
-            .lines()
-            .map(|line| line.chars().peekable())
-            .collect::<Box<[_]>>();
+    /// Parses a levels file after fixing the mistakes hand editing often leaves in it, which are
+    /// whitespace after the end of a line, rows missing their closing `|` and blank lines at the
+    /// end. Returns warnings for each fix made, and still fails on anything else.
+    pub fn parse_lenient(s: &str) -> Result<(Self, Vec<ParseWarning>), LocatedParseLevelError> {
+        let mut warnings = Vec::new();
+        let mut lines = s.lines().map(str::to_owned).collect::<Vec<_>>();
+
+        if lines.last().is_some_and(|line| line.trim().is_empty()) {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
 
-        if lines.len() != Self::LEVEL_HEIGHT {
-            return Err(ParseLevelError::InvalidHeight);
+            warnings.push(ParseWarning::TrailingBlankLines);
         }
 
-        loop {
-            for (i, line) in lines.iter_mut().enumerate().rev() {
-                let Some(character) = line.next() else {
-                    return Err(ParseLevelError::LineEndsEarly(i));
-                };
+        let grid_start = lines
+            .iter()
+            .position(|line| !line.starts_with('#'))
+            .unwrap_or(lines.len());
+
+        for (i, line) in lines[..grid_start].iter_mut().enumerate() {
+            if line.ends_with(char::is_whitespace) {
+                line.truncate(line.trim_end().len());
+                warnings.push(ParseWarning::TrailingWhitespace(i));
+            }
+        }
+
+        // Whitespace is empty tiles inside the grid, so only whitespace after the closing `|` can
+        // be removed
+        for (i, line) in lines.iter_mut().enumerate().skip(grid_start) {
+            if let Some(end) = line.rfind('|')
+                && end + 1 < line.len()
+                && line[end + 1..].trim().is_empty()
+            {
+                line.truncate(end + 1);
+                warnings.push(ParseWarning::TrailingWhitespace(i));
+            }
+        }
+
+        let width = lines[grid_start..]
+            .iter()
+            .map(|line| line.trim_end_matches('|').chars().count())
+            .max()
+            .unwrap_or(0);
+
+        for (i, line) in lines.iter_mut().enumerate().skip(grid_start) {
+            if !line.ends_with('|') {
+                let padding = width - line.chars().count();
+
+                line.extend(std::iter::repeat_n(' ', padding));
+                line.push('|');
+                warnings.push(ParseWarning::MissingTerminator(i));
+            }
+        }
+
+        let text = lines
+            .into_iter()
+            .map(|line| line + "\n")
+            .collect::<String>();
+
+        Self::parse_located(&text).map(|levels| (levels, warnings))
+    }
+
+    /// Finds problems which don't stop the levels from being read but would stop them from being
+    /// played
+    #[must_use]
+    pub fn problems(&self) -> Vec<LevelProblem> {
+        (0..self.num_levels)
+            .filter(|level_index| {
+                // The player enters levels on the left edge, and can only start there on the
+                // empty side
+                let start = Self::offset_of_level(*level_index);
+
+                self.tiles[start..start + Self::LEVEL_HEIGHT]
+                    .iter()
+                    .all(|tile| tile.is_solid())
+            })
+            .map(LevelProblem::NoEntrance)
+            .collect()
+    }
+}
+
+/// A line and column in a text file, counting from 1 like text editors do
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Parses a levels file, keeping `position` at the part of the text being read so that it points
+/// at the problem if there is an error
+fn parse_levels(s: &str, position: &mut TextPosition) -> Result<Levels, ParseLevelError> {
+    let mut tiles = Vec::new();
+
+    let mut limited_gem = None;
+    let mut full_gem = None;
 
-                let tile = match character {
-                    ' ' => false,
-                    'x' => true,
-                    'e' => {
-                        if limited_gem.is_none() {
-                            if tiles.last() == Some(&true) {
-                                limited_gem = Some(tiles.len());
-                            } else {
-                                return Err(ParseLevelError::InvalidTileBelowGem);
-                            }
+    let mut lines = s.lines().peekable();
+
+    // The number of levels isn't known until the tiles are read, so the metadata is kept by level
+    // index until then, along with the line of the last level it's for
+    let mut metadata = BTreeMap::new();
+    let mut current_level = None;
+    let mut last_level = None;
+    let mut header_lines = 0;
+
+    while let Some(line) = lines.next_if(|line| line.starts_with('#')) {
+        *position = TextPosition {
+            line: header_lines + 1,
+            column: 1,
+        };
+
+        parse_header_line(line, header_lines, &mut metadata, &mut current_level)?;
+
+        if let Some(level_index) = current_level
+            && last_level.is_none_or(|(last_index, _)| level_index > last_index)
+        {
+            last_level = Some((level_index, *position));
+        }
+
+        header_lines += 1;
+    }
+
+    let mut lines = lines
+        .map(|line| line.chars().peekable())
+        .collect::<Box<[_]>>();
+
+    *position = TextPosition {
+        line: header_lines + 1,
+        column: 1,
+    };
+
+    if lines.len() != Levels::LEVEL_HEIGHT {
+        return Err(ParseLevelError::InvalidHeight);
+    }
+
+    let mut column = 1;
+
+    loop {
+        for (i, line) in lines.iter_mut().enumerate().rev() {
+            *position = TextPosition {
+                line: header_lines + i + 1,
+                column,
+            };
+
+            let Some(character) = line.next() else {
+                return Err(ParseLevelError::LineEndsEarly(i));
+            };
+
+            let tile = match character {
+                'e' => {
+                    if limited_gem.is_none() {
+                        if tiles.last().is_some_and(|tile: &Tile| tile.is_solid()) {
+                            limited_gem = Some(tiles.len());
                         } else {
-                            return Err(ParseLevelError::DuplicateGem('e'));
+                            return Err(ParseLevelError::InvalidTileBelowGem);
                         }
-
-                        false
+                    } else {
+                        return Err(ParseLevelError::DuplicateGem('e'));
                     }
-                    'E' => {
-                        if full_gem.is_none() {
-                            if tiles.last() == Some(&true) {
-                                full_gem = Some(tiles.len());
-                            } else {
-                                return Err(ParseLevelError::InvalidTileBelowGem);
-                            }
+
+                    Tile::Empty
+                }
+                'E' => {
+                    if full_gem.is_none() {
+                        if tiles.last().is_some_and(|tile: &Tile| tile.is_solid()) {
+                            full_gem = Some(tiles.len());
                         } else {
-                            return Err(ParseLevelError::DuplicateGem('E'));
+                            return Err(ParseLevelError::InvalidTileBelowGem);
                         }
-
-                        false
+                    } else {
+                        return Err(ParseLevelError::DuplicateGem('E'));
                     }
-                    character => {
-                        return Err(ParseLevelError::InvalidTileCharacter(character));
-                    }
-                };
 
-                tiles.push(tile);
-            }
+                    Tile::Empty
+                }
+                character => Tile::from_character(character)
+                    .ok_or(ParseLevelError::InvalidTileCharacter(character))?,
+            };
 
-            if lines[0].peek() == Some(&'|') {
-                for (i, mut line) in lines.into_iter().enumerate() {
-                    let next = line.next();
+            tiles.push(tile);
+        }
 
-                    match next {
-                        Some('|') => {
-                            if line.next().is_some() {
-                                return Err(ParseLevelError::InvalidTileCharacter('|'));
-                            }
-                        }
-                        Some(character) => {
-                            return Err(ParseLevelError::InvalidEndingCharacter(character));
-                        }
-                        None => {
-                            return Err(ParseLevelError::LineEndsEarly(i));
+        column += 1;
+
+        if lines[0].peek() == Some(&'|') {
+            for (i, mut line) in lines.into_iter().enumerate() {
+                *position = TextPosition {
+                    line: header_lines + i + 1,
+                    column,
+                };
+
+                let next = line.next();
+
+                match next {
+                    Some('|') => {
+                        if line.next().is_some() {
+                            return Err(ParseLevelError::InvalidTileCharacter('|'));
                         }
                     }
+                    Some(character) => {
+                        return Err(ParseLevelError::InvalidEndingCharacter(character));
+                    }
+                    None => {
+                        return Err(ParseLevelError::LineEndsEarly(i));
+                    }
                 }
+            }
+
+            break;
+        }
+    }
+
+    *position = TextPosition {
+        line: header_lines + 1,
+        column,
+    };
 
-                break;
+    if tiles.len() % LEVEL_TILES != 0 {
+        return Err(ParseLevelError::InvalidWidth);
+    }
+
+    let num_levels = tiles.len() / LEVEL_TILES;
+
+    if let Some((level_index, level_position)) = last_level
+        && level_index >= num_levels
+    {
+        *position = level_position;
+
+        return Err(ParseLevelError::MetadataForMissingLevel(level_index + 1));
+    }
+
+    let metadata = (0..num_levels)
+        .map(|level_index| metadata.remove(&level_index).unwrap_or_default())
+        .collect();
+
+    Ok(Levels {
+        tiles,
+        num_levels,
+        level_index: 0,
+        x_offset: 0,
+        limited_gem,
+        full_gem,
+        metadata,
+        switched: BTreeSet::new(),
+    })
+}
+
+/// Parses one line of the header of a levels file, where `line_index` is its index in the file.
+/// The first line must be the version, and each level's metadata follows a `#level` line with
+/// the level's number, which sets `current_level`.
+fn parse_header_line(
+    line: &str,
+    line_index: usize,
+    metadata: &mut BTreeMap<usize, LevelMetadata>,
+    current_level: &mut Option<usize>,
+) -> Result<(), ParseLevelError> {
+    let invalid = ParseLevelError::InvalidHeaderLine(line_index);
+
+    let line = line.strip_prefix('#').ok_or(invalid)?;
+    let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+
+    if (line_index == 0) != (key == "version") {
+        return Err(invalid);
+    }
+
+    match key {
+        "version" => {
+            let version = value.parse::<u32>().map_err(|_| invalid)?;
+
+            if version == 0 || version > Levels::FORMAT_VERSION {
+                return Err(ParseLevelError::UnsupportedVersion(version));
             }
         }
+        "level" => {
+            let level_number = value.parse::<usize>().map_err(|_| invalid)?;
 
-        const LEVEL_TILES: usize = (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT;
+            if level_number == 0 {
+                return Err(invalid);
+            }
 
-        if tiles.len() % LEVEL_TILES != 0 {
-            return Err(ParseLevelError::InvalidWidth);
+            metadata.entry(level_number - 1).or_default();
+            *current_level = Some(level_number - 1);
         }
+        _ => {
+            let level_metadata = metadata
+                .get_mut(&current_level.ok_or(invalid)?)
+                .ok_or(invalid)?;
+            let physics = &mut level_metadata.physics;
+            let parse_number = || value.parse::<f32>().map_err(|_| invalid);
+            let parse_scale = || {
+                Some(parse_number()?)
+                    .filter(|scale| PhysicsOverrides::is_valid_scale(*scale))
+                    .ok_or(invalid)
+            };
+
+            match key {
+                "name" => level_metadata.name = Some(value.to_owned()),
+                "author" => level_metadata.author = Some(value.to_owned()),
+                "par_time" => level_metadata.par_time = Some(parse_number()?),
+                "gravity_scale" => physics.gravity_scale = Some(parse_scale()?),
+                "friction_scale" => physics.friction_scale = Some(parse_scale()?),
+                "jump_scale" => physics.jump_scale = Some(parse_scale()?),
+                "switch_region" => {
+                    let coordinates = value
+                        .split_whitespace()
+                        .map(|coordinate| coordinate.parse::<usize>().map_err(|_| invalid))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    let [x1, y1, x2, y2] = coordinates[..] else {
+                        return Err(invalid);
+                    };
+
+                    if !is_valid_switch_region([[x1, y1], [x2, y2]]) {
+                        return Err(invalid);
+                    }
 
-        let num_levels = tiles.len() / LEVEL_TILES;
+                    level_metadata.switch_region = Some([[x1, y1], [x2, y2]]);
+                }
+                _ => {
+                    let pickup = Pickup::ALL
+                        .into_iter()
+                        .find(|pickup| key.strip_suffix("_radius") == Some(pickup.name()))
+                        .ok_or(invalid)?;
+                    let radius = parse_number()?;
+
+                    if !PhysicsOverrides::is_valid_pickup_radius(radius) {
+                        return Err(invalid);
+                    }
 
-        Ok(Self {
-            tiles,
-            num_levels,
-            level_index: 0,
-            x_offset: 0,
-            limited_gem,
-            full_gem,
-            animation: 0.0,
-        })
+                    physics.pickup_radii[pickup.index()] = Some(radius);
+                }
+            }
+        }
     }
+
+    Ok(())
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -356,4 +1012,129 @@ pub enum ParseLevelError {
     LineEndsEarly(usize),
     DuplicateGem(char),
     InvalidTileBelowGem,
+    UnsupportedVersion(u32),
+    InvalidHeaderLine(usize),
+    MetadataForMissingLevel(usize),
+    InvalidGemPosition(char),
+    InvalidMetadata(usize),
+}
+
+impl Display for ParseLevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseLevelError::InvalidHeight => {
+                write!(f, "levels must be {} tiles tall", Levels::LEVEL_HEIGHT)
+            }
+            ParseLevelError::InvalidWidth => write!(
+                f,
+                "levels must be a multiple of {} tiles wide",
+                Levels::LEVEL_WIDTH - 1,
+            ),
+            ParseLevelError::InvalidTileCharacter(character) => {
+                write!(f, "invalid tile character {character:?}")
+            }
+            ParseLevelError::InvalidEndingCharacter(character) => {
+                write!(f, "expected a line to end with '|' but found {character:?}")
+            }
+            ParseLevelError::LineEndsEarly(row) => write!(f, "row {} ends early", row + 1),
+            ParseLevelError::DuplicateGem(character) => {
+                write!(f, "there is more than one {character:?} gem")
+            }
+            ParseLevelError::InvalidTileBelowGem => write!(f, "gems must be on top of solid tiles"),
+            ParseLevelError::UnsupportedVersion(version) => write!(
+                f,
+                "version {version} is not supported, the latest is {}",
+                Levels::FORMAT_VERSION,
+            ),
+            ParseLevelError::InvalidHeaderLine(line) => {
+                write!(f, "invalid header on line {}", line + 1)
+            }
+            ParseLevelError::MetadataForMissingLevel(level_number) => {
+                write!(
+                    f,
+                    "there is metadata for level {level_number}, which doesn't exist"
+                )
+            }
+            ParseLevelError::InvalidGemPosition(character) => {
+                write!(
+                    f,
+                    "the {character:?} gem isn't in an empty tile in the levels"
+                )
+            }
+            ParseLevelError::InvalidMetadata(level_number) => {
+                write!(f, "the metadata for level {level_number} is invalid")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
+
+/// A [`ParseLevelError`] and where it was found, which displays the line it's on with a caret
+/// under the problem
+#[derive(Clone, Debug)]
+pub struct LocatedParseLevelError {
+    pub error: ParseLevelError,
+    pub position: TextPosition,
+    /// The text of the line the error was found on
+    pub line: String,
+}
+
+impl Display for LocatedParseLevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let TextPosition { line, column } = self.position;
+
+        writeln!(f, "line {line}, column {column}: {}", self.error)?;
+        writeln!(f, "    {}", self.line)?;
+        write!(f, "    {}^", " ".repeat(column - 1))
+    }
+}
+
+impl std::error::Error for LocatedParseLevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A mistake fixed by [`Levels::parse_lenient`], by the index of the line it was on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseWarning {
+    TrailingWhitespace(usize),
+    MissingTerminator(usize),
+    TrailingBlankLines,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::TrailingWhitespace(line) => {
+                write!(f, "removed whitespace from the end of line {}", line + 1)
+            }
+            ParseWarning::MissingTerminator(line) => {
+                write!(f, "added the missing '|' to the end of line {}", line + 1)
+            }
+            ParseWarning::TrailingBlankLines => {
+                write!(f, "removed blank lines from the end of the file")
+            }
+        }
+    }
+}
+
+/// Something which makes a level unplayable, by the level's index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelProblem {
+    /// Every tile on the level's left edge is solid, so the player has nowhere to enter it
+    NoEntrance(usize),
+}
+
+impl Display for LevelProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelProblem::NoEntrance(level_index) => write!(
+                f,
+                "level {} has no empty tile on its left edge to enter at",
+                level_index + 1
+            ),
+        }
+    }
 }
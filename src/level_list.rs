@@ -0,0 +1,215 @@
+use macroquad::{
+    color::colors,
+    input::{self, KeyCode},
+    shapes,
+};
+
+use inverse::keybinds::{Action, Keybinds};
+use inverse::level::Levels;
+
+use crate::controls;
+use crate::text::{self, TextSize};
+
+/// The most levels shown at once, scrolling to keep the focused one in view
+const VISIBLE_ROWS: usize = 9;
+const ROW_HEIGHT: f32 = 0.75;
+const ROW_WIDTH: f32 = 10.0;
+
+/// The full editor's panel for rearranging, duplicating and renaming levels
+#[derive(Clone, Debug)]
+pub struct LevelList {
+    pub focus: usize,
+    /// The first level shown
+    scroll: usize,
+    /// The new name being typed for the focused level, if it's being renamed
+    renaming: Option<String>,
+}
+
+/// A change to the levels chosen in the [`LevelList`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LevelListAction {
+    Move {
+        from: usize,
+        to: usize,
+    },
+    Duplicate(usize),
+    /// Renames a level, or clears its name if given `None`
+    Rename(usize, Option<String>),
+    Close,
+}
+
+impl LevelList {
+    /// Opens the panel focused on the current level
+    pub fn new(levels: &Levels) -> Self {
+        Self {
+            focus: levels.level_index,
+            scroll: 0,
+            renaming: None,
+        }
+    }
+
+    /// Returns `true` if a name is being typed, which other keys shouldn't respond to
+    #[must_use]
+    pub fn is_renaming(&self) -> bool {
+        self.renaming.is_some()
+    }
+
+    /// Draws the panel and handles its input through the level list's keybinds, which move the
+    /// focus, or the focused level along with it, and duplicate or start renaming the focused level
+    pub fn update(&mut self, levels: &Levels, keybinds: &Keybinds) -> Option<LevelListAction> {
+        let num_levels = levels.num_levels;
+        self.focus = self.focus.min(num_levels - 1);
+
+        let action = if let Some(name) = &mut self.renaming {
+            match update_name(name) {
+                NameInput::Typing => None,
+                NameInput::Cancelled => {
+                    self.renaming = None;
+                    None
+                }
+                NameInput::Finished(name) => {
+                    self.renaming = None;
+                    Some(LevelListAction::Rename(self.focus, name))
+                }
+            }
+        } else {
+            self.update_focus(levels, keybinds)
+        };
+
+        self.scroll = self
+            .scroll
+            .clamp((self.focus + 1).saturating_sub(VISIBLE_ROWS), self.focus);
+
+        self.draw(levels, keybinds);
+
+        action
+    }
+
+    fn update_focus(&mut self, levels: &Levels, keybinds: &Keybinds) -> Option<LevelListAction> {
+        let num_levels = levels.num_levels;
+        let up = keybinds.is_pressed(Action::ListUp);
+        let down = keybinds.is_pressed(Action::ListDown);
+
+        if input::is_key_pressed(KeyCode::Escape) {
+            return Some(LevelListAction::Close);
+        }
+
+        if up || down {
+            let from = self.focus;
+            let to = if up {
+                from.checked_sub(1)?
+            } else {
+                Some(from + 1).filter(|to| *to < num_levels)?
+            };
+
+            self.focus = to;
+
+            if keybinds.is_down(Action::MoveLevel) {
+                return Some(LevelListAction::Move { from, to });
+            }
+        }
+
+        if keybinds.is_pressed(Action::DuplicateLevel) {
+            return Some(LevelListAction::Duplicate(self.focus));
+        }
+
+        if keybinds.is_pressed(Action::RenameLevel) {
+            self.renaming = Some(levels.metadata[self.focus].name.clone().unwrap_or_default());
+        }
+
+        None
+    }
+
+    fn draw(&self, levels: &Levels, keybinds: &Keybinds) {
+        text::draw_centered("LEVELS", [0.0, 4.5], TextSize::Large, colors::WHITE);
+
+        let visible = self.scroll..(self.scroll + VISIBLE_ROWS).min(levels.num_levels);
+
+        for (row, level_index) in visible.enumerate() {
+            let y = 3.25 - row as f32 * ROW_HEIGHT;
+            let focused = level_index == self.focus;
+
+            let name = match &self.renaming {
+                Some(name) if focused => format!("{name}_"),
+                _ => levels.metadata[level_index]
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| "UNTITLED".to_owned()),
+            };
+            let current = if level_index == levels.level_index {
+                '>'
+            } else {
+                ' '
+            };
+            let label = format!("{current}{:>3}  {}", level_index + 1, name.to_uppercase());
+
+            let color = if focused {
+                shapes::draw_rectangle(
+                    -ROW_WIDTH / 2.0,
+                    y - ROW_HEIGHT / 2.0,
+                    ROW_WIDTH,
+                    ROW_HEIGHT,
+                    colors::WHITE,
+                );
+
+                colors::BLACK
+            } else {
+                colors::WHITE
+            };
+
+            text::draw_left_aligned(&label, [-ROW_WIDTH / 2.0 + 0.25, y], TextSize::Small, color);
+        }
+
+        let hint = if self.is_renaming() {
+            "ENTER TO RENAME, ESCAPE TO CANCEL".to_owned()
+        } else {
+            let key = |action| {
+                keybinds
+                    .bindings(action)
+                    .first()
+                    .map_or("NONE".to_owned(), |binding| {
+                        controls::binding_label(*binding)
+                    })
+            };
+
+            format!(
+                "{} RENAME  {} DUPLICATE  HOLD {} TO MOVE",
+                key(Action::RenameLevel),
+                key(Action::DuplicateLevel),
+                key(Action::MoveLevel),
+            )
+        };
+
+        text::draw_centered(&hint, [0.0, -4.5], TextSize::Small, colors::GRAY);
+    }
+}
+
+enum NameInput {
+    Typing,
+    Cancelled,
+    /// The name to give the level, or `None` if it was left empty
+    Finished(Option<String>),
+}
+
+/// Types into a level's new name
+fn update_name(name: &mut String) -> NameInput {
+    while let Some(character) = input::get_char_pressed() {
+        if !character.is_control() {
+            name.push(character);
+        }
+    }
+
+    if input::is_key_pressed(KeyCode::Backspace) {
+        name.pop();
+    }
+
+    if input::is_key_pressed(KeyCode::Escape) {
+        NameInput::Cancelled
+    } else if input::is_key_pressed(KeyCode::Enter) {
+        let name = name.trim();
+
+        NameInput::Finished((!name.is_empty()).then(|| name.to_owned()))
+    } else {
+        NameInput::Typing
+    }
+}
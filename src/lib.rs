@@ -0,0 +1,29 @@
+/// Profiles the rest of the enclosing block when the `profile` feature is enabled
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profile")]
+        puffin::profile_scope!($name);
+    };
+}
+
+pub mod animation;
+pub mod changelog;
+pub mod debug_flags;
+pub mod editor;
+pub mod input_script;
+pub mod keybinds;
+pub mod level;
+pub mod physics;
+pub mod pickup;
+pub mod player;
+pub mod savegame;
+pub mod speedrun;
+pub mod storage;
+pub mod tile;
+pub mod timestep;
+
+use crate::level::Levels;
+
+pub const LOGICAL_SCREEN_WIDTH: f32 = Levels::LEVEL_WIDTH as f32;
+pub const LOGICAL_SCREEN_HEIGHT: f32 = Levels::LEVEL_HEIGHT as f32;
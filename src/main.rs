@@ -1,30 +1,65 @@
-pub mod level;
-pub mod player;
-
-use std::{array, f32::consts::TAU, fs};
+pub mod console;
+pub mod controls;
+pub mod crash;
+pub mod debug;
+pub mod digits;
+pub mod level_list;
+#[cfg(feature = "profile")]
+pub mod profile;
+#[cfg(feature = "status-file")]
+pub mod status;
+pub mod text;
+pub mod title;
+pub mod ui;
+
+use std::{
+    f32::consts::TAU,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use macroquad::{
     camera::{self, Camera2D},
     color::{Color, colors},
     input::{self, KeyCode, MouseButton},
+    math::Vec2,
     shapes::{self, DrawRectangleParams},
-    text::{self, TextDimensions, TextParams},
     window::{self, Conf},
 };
 
-use crate::level::Levels;
-use crate::player::Player;
+use inverse::animation::{AnimationClocks, Easing, Timeline};
+use inverse::changelog::Changelog;
+use inverse::editor::{self, Clipboard, Drag, Editor, Gem, Shape};
+use inverse::keybinds::{Action, KEYBINDS_FILE_NAME, Keybinds};
+use inverse::level::Levels;
+use inverse::pickup::{Pickup, PickupTrigger};
+use inverse::player::Player;
+use inverse::savegame::{SAVE_FILE_NAME, SaveGame};
+use inverse::speedrun::{self, SpeedrunTimer};
+use inverse::storage::{self, Autosave, TrackedFile};
+use inverse::tile::Tile;
+use inverse::timestep::FixedTimestep;
+use inverse::{LOGICAL_SCREEN_HEIGHT, LOGICAL_SCREEN_WIDTH, profile_scope};
+
+use crate::console::Console;
+use crate::controls::ControlsMenu;
+use crate::debug::DebugOverlay;
+use crate::level_list::{LevelList, LevelListAction};
+use crate::text::TextSize;
+use crate::title::{Screen, ScreenAction};
+use crate::ui::{Menu, MenuState};
 
 const START_IN_FULLSCREEN: bool = false;
 const SCREEN_WIDTH: f32 = LOGICAL_SCREEN_WIDTH;
 const SCREEN_HEIGHT: f32 = LOGICAL_SCREEN_HEIGHT + 0.25;
 const SCREEN_ASPECT: f32 = SCREEN_WIDTH / SCREEN_HEIGHT;
 
-const LOGICAL_SCREEN_WIDTH: f32 = Levels::LEVEL_WIDTH as f32;
-const LOGICAL_SCREEN_HEIGHT: f32 = Levels::LEVEL_HEIGHT as f32;
-
-const PATH_TO_LEVELS: &str = "levels.txt";
+const LEVELS_FILE_NAME: &str = "levels.txt";
+const ORIGINAL_LEVELS: &str = include_str!("../original_levels.txt");
+const CHANGELOG: &str = include_str!("../changelog.txt");
 const CHEAT_CODE: &str = "413 38D";
+/// How long a timed run can go without input before it pauses itself
+const IDLE_SECONDS: f32 = 30.0;
 
 fn window_conf() -> Conf {
     Conf {
@@ -36,6 +71,16 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
+    crash::install_hook();
+    // Closing the window goes through the same path as quitting from the menus, so unsaved level
+    // edits get written first
+    input::prevent_quit();
+
+    if let Err(error) = storage::back_up_on_version_change(&[LEVELS_FILE_NAME, SAVE_FILE_NAME]) {
+        eprintln!("couldn't back up the files from the last version: {error}");
+    }
+    let latest_backup = storage::latest_backup();
+
     let mut fullscreen = START_IN_FULLSCREEN;
 
     let mut camera = Camera2D::default();
@@ -44,105 +89,404 @@ async fn main() {
         last_selected: None,
     };
     let mut editor_enabled = false;
+    let mut clipboard = Clipboard::default();
     let mut gems_active = false;
 
+    // Gems bob up and down and spin, while the player shrinks away when they die
+    let gem_bob = Timeline::new(0.0)
+        .then(2.0, 0.125, Easing::SineOut)
+        .then(4.0, -0.125, Easing::SineInOut)
+        .then(2.0, 0.0, Easing::SineIn)
+        .looping();
+    let gem_spin = Timeline::new(0.0).then(6.0, TAU, Easing::Linear).looping();
+    let death_shrink = Timeline::new(Player::SIZE).then(
+        Player::DEATH_FRAMES as f32 / Player::UPDATES_PER_SECOND,
+        0.0,
+        Easing::Linear,
+    );
+    // Keyed by the index of each gem's tile, and wrapping at a multiple of both gem timelines
+    let mut gem_clocks = AnimationClocks::new(24.0);
+
     let mut cheat_code = Some(String::new());
 
+    let mut screen = Screen::title();
+    let mut paused = false;
+    let mut pause_menu = MenuState::default();
+    let mut game_speed = 1.0;
+    let mut keep_velocity_on_inversion = false;
+    let mut clean_output = false;
+    let mut show_speedrun_timer = false;
+
+    let mut debug_overlay = DebugOverlay::default();
+    let mut console = Console::new();
+
+    // Settings and saves which can't be loaded are kept aside rather than overwritten
+    let mut keybinds_file = TrackedFile::new(KEYBINDS_FILE_NAME);
+    let mut keybinds = Keybinds::load(&mut keybinds_file).unwrap_or_else(|error| {
+        set_aside_unloadable(&mut keybinds_file, &error);
+        Keybinds::default()
+    });
+    let mut save_file = TrackedFile::new(SAVE_FILE_NAME);
+    let mut savegame = SaveGame::load(&mut save_file).unwrap_or_else(|error| {
+        set_aside_unloadable(&mut save_file, &error);
+        SaveGame::default()
+    });
+
+    if let Some(unlocked_editor) = savegame.unlocked_editor() {
+        editor = unlocked_editor;
+        editor_enabled = true;
+    }
+    let mut controls_menu: Option<ControlsMenu> = None;
+    let mut level_list: Option<LevelList> = None;
+
+    // Show the changes since the player last played once after the game updates, but not to new
+    // players
+    let version = env!("CARGO_PKG_VERSION");
+
+    if savegame.seen_version.as_deref() != Some(version) {
+        let changelog = CHANGELOG.parse::<Changelog>().unwrap();
+        let releases = changelog.since(savegame.seen_version.as_deref().unwrap_or_default());
+
+        if savegame != SaveGame::default() && !releases.is_empty() {
+            screen = Screen::WhatsNew {
+                releases: releases.to_vec(),
+            };
+        }
+
+        savegame.seen_version = Some(version.to_owned());
+        let _ = savegame.save(&mut save_file);
+    }
+
+    let mut levels_file = TrackedFile::new(LEVELS_FILE_NAME);
+
+    #[cfg(feature = "status-file")]
+    let mut status_file = status::StatusFile::new();
+
+    #[cfg(feature = "profile")]
+    let mut profiler = profile::Profiler::new();
+
     loop {
-        let mut levels = fs::read_to_string(PATH_TO_LEVELS)
-            .unwrap()
-            .parse::<Levels>()
-            .unwrap();
-        let mut player = Player::new(false);
+        // Hand edited files are fixed up where possible, and written back properly the next
+        // time the levels change
+        let (mut levels, warnings) = Levels::parse_lenient(&read_levels(&mut levels_file))
+            .unwrap_or_else(|error| panic!("invalid levels file, {error}"));
+
+        for warning in warnings {
+            eprintln!("{LEVELS_FILE_NAME}: {warning}");
+        }
+        let mut player = Player::new(keep_velocity_on_inversion);
+        savegame.resume(&mut levels, &mut player);
 
-        let mut update_time = 0.0;
+        let mut timestep = FixedTimestep::default();
+        // The number of updates the player has spent in the current level, and whether debug flags
+        // have been on during any of them
+        let mut level_updates = 0;
+        let mut level_used_debug_flags = false;
+        let mut speedrun_timer = SpeedrunTimer::default();
 
         let mut reset_button_time = 0.0;
+        // The time since the last input, which pauses timed runs once it reaches IDLE_SECONDS
+        let mut idle_time = 0.0;
+        let mut confirmation: Option<(PendingAction, MenuState)> = None;
+        // Edits are written in batches rather than after every tile. Anything which reloads the
+        // levels throws away unsaved edits, but those all replace the file on purpose.
+        let mut autosave = Autosave::default();
+        // For the limited and full gems, so each touch of one only toggles the editor once
+        let mut gem_triggers = [PickupTrigger::default(); 2];
 
         loop {
-            if let Some(code) = &mut cheat_code {
-                if let Some(character) = input::get_char_pressed() {
-                    code.push(character);
-
-                    if code.len() >= CHEAT_CODE.len() {
-                        if code == CHEAT_CODE {
-                            editor = Editor::Full;
-                            editor_enabled = true;
-                        }
+            #[cfg(feature = "profile")]
+            profiler.new_frame();
+
+            // Escape closes the console, which shouldn't also pause the game
+            let console_was_open = console.open;
+
+            if input::is_key_pressed(KeyCode::GraveAccent) {
+                console.open ^= true;
+            }
+
+            if console.open {
+                let mut context = console::Context {
+                    levels: &mut levels,
+                    player: &mut player,
+                    editor: &mut editor,
+                    editor_enabled: &mut editor_enabled,
+                    savegame: &mut savegame,
+                    levels_file: &mut levels_file,
+                    save_file: &mut save_file,
+                    debug_overlay: &mut debug_overlay,
+                    reload: false,
+                    levels_changed: false,
+                };
+
+                console.update(&mut context);
 
-                        cheat_code = None;
+                if context.levels_changed {
+                    autosave.mark_dirty();
+                }
+
+                if context.reload {
+                    break;
+                }
+            }
+
+            // Keys pressed while rebinding, or typing into the console or a level's name,
+            // shouldn't do anything else
+            let capturing_keys = console.open
+                || controls_menu
+                    .as_ref()
+                    .is_some_and(|controls_menu| controls_menu.rebinding.is_some())
+                || level_list.as_ref().is_some_and(LevelList::is_renaming);
+
+            if let Some(code) = &mut cheat_code
+                && !capturing_keys
+                && let Some(character) = input::get_char_pressed()
+            {
+                code.push(character);
+
+                if code.len() >= CHEAT_CODE.len() {
+                    if code == CHEAT_CODE {
+                        editor = Editor::Full { drag: None };
+                        editor_enabled = true;
                     }
+
+                    cheat_code = None;
                 }
             }
 
-            if input::is_key_pressed(KeyCode::F11) {
+            if !capturing_keys && keybinds.is_pressed(Action::Fullscreen) {
                 fullscreen ^= true;
                 window::set_fullscreen(fullscreen);
             }
 
-            if editor_enabled {
-                if input::is_mouse_button_pressed(MouseButton::Left) {
-                    let mouse_position =
-                        <[f32; 2]>::from(camera.screen_to_world(input::mouse_position().into()));
+            if !capturing_keys && keybinds.is_pressed(Action::DebugOverlay) {
+                debug_overlay.enabled ^= true;
+            }
 
-                    let mouse_position = [
-                        mouse_position[0] + LOGICAL_SCREEN_WIDTH / 2.0,
-                        mouse_position[1] + LOGICAL_SCREEN_HEIGHT / 2.0,
-                    ];
+            #[cfg(feature = "profile")]
+            if input::is_key_pressed(KeyCode::F3) {
+                profiler.show_flamegraph ^= true;
+            }
 
-                    if let Ok(mouse_index) = levels.index_of_position(mouse_position) {
-                        let tile_index = levels.index_of(mouse_index).unwrap();
+            // The clipboard only works in the full editor, though what was copied is kept
+            if !(editor_enabled && editor.is_full()) {
+                clipboard.pasting = false;
+                clipboard.selection = None;
+            }
 
-                        if editor.toggle_tile_index(tile_index, &mut levels, &mut player) {
-                            fs::write(PATH_TO_LEVELS, levels.to_string()).unwrap();
-                        }
+            let escape_pressed = input::is_key_pressed(KeyCode::Escape)
+                && !console_was_open
+                && !console.open
+                && screen.is_playing()
+                && confirmation.is_none()
+                && controls_menu.is_none()
+                && level_list.is_none();
+
+            // Escape lets go of what's being pasted or selected before it pauses the game
+            if escape_pressed && (paused || !clipboard.cancel()) {
+                paused ^= true;
+                pause_menu.focus = 0;
+            }
+
+            if has_any_input() {
+                idle_time = 0.0;
+            } else {
+                idle_time += macroquad::time::get_frame_time();
+            }
+
+            // Timed runs stop while the player is away, so the time doesn't count against them,
+            // until they press anything
+            let idle = idle_time >= IDLE_SECONDS && speedrun_timer.is_running();
+
+            let modal_open = paused
+                || confirmation.is_some()
+                || level_list.is_some()
+                || !screen.is_playing()
+                || console.open
+                || idle;
+
+            if editor_enabled
+                && editor.is_full()
+                && !modal_open
+                && !capturing_keys
+                && keybinds.is_pressed(Action::LevelList)
+            {
+                level_list = Some(LevelList::new(&levels));
+            }
+
+            let placing_gems = editor_enabled
+                && editor.is_full()
+                && !modal_open
+                && !capturing_keys
+                && !clipboard.pasting
+                && keybinds.is_down(Action::PlaceGems);
+
+            // Clicking while placing gems moves the limited gem, or the full gem with the right
+            // mouse button, onto the tile under the mouse
+            if placing_gems {
+                let gem = if input::is_mouse_button_pressed(MouseButton::Left) {
+                    Some(Gem::Limited)
+                } else if input::is_mouse_button_pressed(MouseButton::Right) {
+                    Some(Gem::Full)
+                } else {
+                    None
+                };
+
+                if let Some(gem) = gem
+                    && let Some(gem_index) = mouse_gem_index(&camera, &levels)
+                    && editor.place_gem(gem, gem_index, &mut levels, &player)
+                {
+                    autosave.mark_dirty();
+                }
+            } else if editor_enabled
+                && !modal_open
+                && input::is_mouse_button_pressed(MouseButton::Left)
+                && let Some(position) = mouse_tile_position(&camera, &levels)
+            {
+                // The full editor draws rectangles by dragging, lines while holding the draw line
+                // keys, or selects tiles to copy while holding the select keys
+                if clipboard.pasting {
+                    if editor.paste(position, &mut clipboard, &mut levels, &mut player) {
+                        autosave.mark_dirty();
                     }
+                } else if editor.is_full() {
+                    let shape = if keybinds.is_down(Action::Select) {
+                        Shape::Selection
+                    } else if keybinds.is_down(Action::DrawLine) {
+                        Shape::Line
+                    } else {
+                        Shape::Rectangle
+                    };
+
+                    editor.start_drag(position, shape);
+                } else if let Some(tile_index) = levels.index_of(position)
+                    && editor.toggle_tile_index(tile_index, &mut levels, &mut player)
+                {
+                    autosave.mark_dirty();
+                }
+            }
+
+            if let Editor::Full { drag: Some(drag) } = &mut editor
+                && let Some(position) = mouse_tile_position(&camera, &levels)
+            {
+                drag.set_end(position, keybinds.is_down(Action::Constrain));
+            }
+
+            if input::is_mouse_button_released(MouseButton::Left) {
+                if let Some(selection) = editor.finish_selection() {
+                    clipboard.selection = Some(selection);
+                } else if editor.finish_drag(&mut levels, &mut player) {
+                    autosave.mark_dirty();
+                }
+            }
+
+            if editor_enabled && editor.is_full() && !modal_open && !capturing_keys {
+                if keybinds.is_pressed(Action::Copy) {
+                    clipboard.copy(&levels);
+                }
+
+                if keybinds.is_pressed(Action::Paste) {
+                    clipboard.start_pasting();
+                }
+            }
+
+            // Right clicking in the full editor places hazards, or stops pasting
+            if editor_enabled
+                && !modal_open
+                && !placing_gems
+                && input::is_mouse_button_pressed(MouseButton::Right)
+            {
+                if clipboard.pasting {
+                    clipboard.pasting = false;
+                } else if let Some(tile_index) = mouse_tile_position(&camera, &levels)
+                    .and_then(|position| levels.index_of(position))
+                    && editor.place_tile(tile_index, Tile::Spike, &mut levels, &mut player)
+                {
+                    autosave.mark_dirty();
                 }
+            }
 
-                // if input::is_key_pressed(KeyCode::M) {
-                //     editor = match editor {
-                //         Editor::Limited { .. } => {
-                //             editor.force_undo_temporary_actions(&mut levels);
-                //             Editor::Full
-                //         }
-                //         Editor::Full => Editor::Limited {
-                //             last_selected: None,
-                //         },
-                //     }
-                // }
+            let save_pressed = !capturing_keys && keybinds.is_pressed(Action::SaveLevels);
 
-                // if input::is_key_down(KeyCode::RightShift) || input::is_key_down(KeyCode::LeftShift) {
-                //     if input::is_key_pressed(KeyCode::I) {
-                //         levels.insert_level(levels.level_index + 1);
-                //
-                //         fs::write(PATH_TO_LEVELS, levels.to_string()).unwrap();
-                //     }
-                //
-                //     if input::is_key_pressed(KeyCode::R) && levels.num_levels > 1 {
-                //         levels.remove_level((levels.level_index + 1) % levels.num_levels);
-                //
-                //         fs::write(PATH_TO_LEVELS, levels.to_string()).unwrap();
-                //     }
-                // }
+            if (save_pressed && autosave.is_dirty())
+                || autosave.update(macroquad::time::get_frame_time())
+            {
+                // A failed save is tried again at the next autosave, or when quitting
+                if let Err(error) = autosave.save(&mut levels_file, levels.to_string()) {
+                    eprintln!("couldn't save {LEVELS_FILE_NAME}: {error}");
+                }
             }
 
-            // if input::is_key_pressed(KeyCode::N) {
-            //     editor_enabled ^= true;
-            // }
+            let mut quitting = input::is_quit_requested();
+
+            let mut save_changed = false;
+
+            player.debug_flags = savegame.debug_flags;
+
+            if savegame.debug_flags.free_editor && !(editor_enabled && editor.is_full()) {
+                editor.force_undo_temporary_actions(&mut levels);
+                editor = Editor::Full { drag: None };
+                editor_enabled = true;
+            }
+
+            if !modal_open {
+                let updates = timestep.advance(macroquad::time::get_frame_time(), game_speed);
+
+                player.update_input(&keybinds);
+                crash::record_input(player.inputs_down);
+
+                for _ in 0..updates {
+                    profile_scope!("update");
+
+                    let level_index = levels.level_index;
+
+                    speedrun_timer.update(&levels, &player, savegame.debug_flags.any());
+                    player.update(&mut levels);
+                    debug_overlay.record_tick(&levels, &player);
+
+                    // Gems appear once the player reaches the last level, or has an editor
+                    if levels.level_index == levels.num_levels - 1 || editor_enabled {
+                        gems_active = true;
+                    }
+
+                    if gems_active {
+                        save_changed |= update_gems(
+                            &mut levels,
+                            &player,
+                            &mut editor,
+                            &mut editor_enabled,
+                            &mut savegame,
+                            &mut gem_triggers,
+                        );
+                    }
 
-            update_time += macroquad::time::get_frame_time() * Player::UPDATES_PER_SECOND;
-            let updates = (update_time as usize).min(Player::MAXIMUM_UPDATES_PER_FRAME);
+                    level_updates += 1;
+                    level_used_debug_flags |= savegame.debug_flags.any();
+
+                    // Only count the time when the player goes forwards through the level
+                    if levels.level_index != level_index {
+                        if levels.level_index == (level_index + 1) % levels.num_levels {
+                            save_changed |= savegame.record_time(
+                                level_index,
+                                level_updates as f32 / Player::UPDATES_PER_SECOND,
+                                level_used_debug_flags,
+                            );
+                            save_changed |= speedrun_timer.split(level_index, &mut savegame);
+                        }
 
-            player.update_input();
+                        level_updates = 0;
+                        level_used_debug_flags = false;
+                    }
+                }
 
-            for _ in 0..updates {
-                player.update(&mut levels);
+                save_changed |= savegame.record(&levels, &player);
             }
 
-            update_time -= updates as f32;
-            update_time = update_time.min(1.0);
+            crash::record_state(&levels, &player, autosave.is_dirty());
 
-            let [_, window_height] = update_camera(&mut camera);
+            profile_scope!("render");
+
+            let [window_width, window_height] = update_camera(&mut camera);
             camera::set_camera(&camera);
 
             // Clear the background to the color Turbowarp dark mode uses
@@ -165,25 +509,92 @@ async fn main() {
                 colors::BLACK,
             );
 
+            let switched = levels.switched.contains(&levels.level_index);
+
             for x in 0..Levels::LEVEL_WIDTH {
                 for y in 0..Levels::LEVEL_HEIGHT {
-                    if !levels[[x, y]] {
-                        let position = [
-                            x as f32 - SCREEN_WIDTH / 2.0,
-                            y as f32 - LOGICAL_SCREEN_HEIGHT / 2.0,
-                        ];
+                    let position = [
+                        x as f32 - SCREEN_WIDTH / 2.0,
+                        y as f32 - LOGICAL_SCREEN_HEIGHT / 2.0,
+                    ];
+
+                    // Collected coins are left empty
+                    let tile = match levels[[x, y]] {
+                        Tile::Coin
+                            if levels
+                                .index_of([x, y])
+                                .is_some_and(|tile_index| player.coins.contains(&tile_index)) =>
+                        {
+                            Tile::Empty
+                        }
+                        tile => tile,
+                    };
 
-                        shapes::draw_rectangle(position[0], position[1], 1.0, 1.0, colors::WHITE);
+                    draw_tile(tile, position, switched);
+                }
+            }
+
+            if savegame.debug_flags.hitboxes {
+                for x in 0..Levels::LEVEL_WIDTH {
+                    for y in 0..Levels::LEVEL_HEIGHT {
+                        let pickup = match levels[[x, y]] {
+                            Tile::Coin => Pickup::Coin,
+                            Tile::DoubleJump => Pickup::DoubleJump,
+                            _ => continue,
+                        };
+
+                        shapes::draw_circle_lines(
+                            x as f32 + 0.5 - SCREEN_WIDTH / 2.0,
+                            y as f32 + 0.5 - LOGICAL_SCREEN_HEIGHT / 2.0,
+                            player.physics.pickup_radius(pickup),
+                            0.0625,
+                            colors::RED,
+                        );
                     }
                 }
             }
 
-            // Player
+            if let Editor::Full { drag: Some(drag) } = &editor {
+                if drag.shape == Shape::Selection {
+                    draw_selection(drag);
+                } else {
+                    draw_drag_preview(drag, &levels);
+                }
+            }
+
+            if let Some(selection) = &clipboard.selection {
+                draw_selection(selection);
+            }
+
+            if placing_gems && let Some(gem_index) = mouse_gem_index(&camera, &levels) {
+                draw_gem_preview(gem_index, &levels, &player);
+            }
+
+            if clipboard.pasting
+                && !modal_open
+                && let Some(corner) = mouse_tile_position(&camera, &levels)
+            {
+                for (position, tile) in clipboard.paste_positions(corner) {
+                    draw_preview_tile(position, tile);
+                }
+            }
+
+            // Player, drawn between their last two positions so movement is smooth at any frame
+            // rate, and which shrinks away while dying
+            let player_position = player.interpolated_position(timestep.fraction());
+            let player_size = if player.is_dead() {
+                let death_updates = Player::DEATH_FRAMES - player.death_time;
+
+                death_shrink.sample(death_updates as f32 / Player::UPDATES_PER_SECOND)
+            } else {
+                Player::SIZE
+            };
+
             shapes::draw_rectangle(
-                player.position[0] - Player::SIZE / 2.0 - LOGICAL_SCREEN_WIDTH / 2.0,
-                player.position[1] - Player::SIZE / 2.0 - LOGICAL_SCREEN_HEIGHT / 2.0,
-                Player::SIZE,
-                Player::SIZE,
+                player_position[0] - player_size / 2.0 - LOGICAL_SCREEN_WIDTH / 2.0,
+                player_position[1] - player_size / 2.0 - LOGICAL_SCREEN_HEIGHT / 2.0,
+                player_size,
+                player_size,
                 match player.air_kind {
                     true => colors::WHITE,
                     false => colors::BLACK,
@@ -191,76 +602,46 @@ async fn main() {
             );
 
             // Gems
-            if levels.level_index == levels.num_levels - 1 || editor_enabled {
-                gems_active = true;
-            }
-
             if gems_active {
-                levels.update_animation_counter();
+                let frame_time = macroquad::time::get_frame_time();
 
-                for (gem, is_full_gem) in [(levels.limited_gem, false), (levels.full_gem, true)] {
+                let gems = [(levels.limited_gem, false), (levels.full_gem, true)];
+
+                for (gem, is_full_gem) in gems {
                     let Some(gem_index) = gem else {
                         continue;
                     };
 
-                    let Some(gem_position) = levels.position_of_tile_index(gem_index) else {
-                        continue;
-                    };
+                    gem_clocks.advance(gem_index, frame_time);
+                    let gem_time = gem_clocks.time(&gem_index);
 
                     let enabled = editor_enabled && (!is_full_gem || editor.is_full());
 
-                    let offset = if enabled { -0.5 } else { 0.5 };
-                    let position = [gem_position[0] + 0.5, gem_position[1] + offset];
-
-                    let player_displacement_squared =
-                        array::from_fn::<_, 2, _>(|i| (position[i] - player.position[i]).powi(2));
-
-                    let distance_squared = player_displacement_squared.into_iter().sum::<f32>();
-
-                    if distance_squared < Player::SIZE.powi(2) {
-                        if is_full_gem {
-                            if enabled {
-                                editor = Editor::Limited {
-                                    last_selected: None,
-                                };
-                            } else {
-                                editor_enabled = true;
+                    let Some(position) = levels.gem_center(gem_index, enabled) else {
+                        continue;
+                    };
 
-                                editor.force_undo_temporary_actions(&mut levels);
-                                editor = Editor::Full;
-                            }
-                        } else {
-                            if enabled {
-                                editor_enabled = false;
-
-                                if !editor.is_limited() {
-                                    editor = Editor::Limited {
-                                        last_selected: None,
-                                    };
-                                }
-                            } else {
-                                editor_enabled = true;
-                            }
-                        }
+                    if savegame.debug_flags.hitboxes {
+                        shapes::draw_circle_lines(
+                            position[0] - LOGICAL_SCREEN_WIDTH / 2.0,
+                            position[1] - LOGICAL_SCREEN_HEIGHT / 2.0,
+                            player.physics.pickup_radius(Pickup::Gem),
+                            0.0625,
+                            colors::RED,
+                        );
                     }
 
-                    let enabled = editor_enabled && (!is_full_gem || editor.is_full());
-
-                    let offset = if enabled { -0.5 } else { 0.5 };
-                    let position = [gem_position[0] + 0.5, gem_position[1] + offset];
-
                     shapes::draw_rectangle_ex(
                         position[0] - LOGICAL_SCREEN_WIDTH / 2.0,
-                        position[1] - LOGICAL_SCREEN_HEIGHT / 2.0
-                            + (levels.animation * TAU / 8.0).sin() / 8.0,
+                        position[1] - LOGICAL_SCREEN_HEIGHT / 2.0 + gem_bob.sample(gem_time),
                         0.5,
                         0.5,
                         DrawRectangleParams {
                             offset: [0.5, 0.5].into(),
                             rotation: if enabled {
-                                -levels.animation * TAU / 6.0
+                                -gem_spin.sample(gem_time)
                             } else {
-                                levels.animation * TAU / 6.0
+                                gem_spin.sample(gem_time)
                             },
                             color: if enabled {
                                 colors::WHITE
@@ -272,18 +653,79 @@ async fn main() {
                 }
             }
 
+            // The player's collision box, where they actually are rather than where they're drawn
+            if savegame.debug_flags.hitboxes {
+                shapes::draw_rectangle_lines(
+                    player.position[0] - Player::SIZE / 2.0 - LOGICAL_SCREEN_WIDTH / 2.0,
+                    player.position[1] - Player::SIZE / 2.0 - LOGICAL_SCREEN_HEIGHT / 2.0,
+                    Player::SIZE,
+                    Player::SIZE,
+                    0.0625,
+                    colors::RED,
+                );
+            }
+
+            // Level number
+            if editor_enabled && !clean_output {
+                digits::draw_number(
+                    levels.level_index + 1,
+                    [0.25, LOGICAL_SCREEN_HEIGHT - 0.25],
+                    1.0,
+                    |position| match levels.get_from_position(position).map(Tile::is_solid) {
+                        Some(true) | None => colors::WHITE,
+                        Some(false) => colors::BLACK,
+                    },
+                );
+            }
+
+            // Curse icon and timer, in red so it shows over both kinds of tile
+            if player.is_cursed() && !clean_output {
+                text::draw_centered(
+                    &format!(
+                        "<> {:.1}",
+                        player.curse_time as f32 / Player::UPDATES_PER_SECOND
+                    ),
+                    [
+                        LOGICAL_SCREEN_WIDTH / 2.0 - 1.25,
+                        LOGICAL_SCREEN_HEIGHT / 2.0 - 0.5,
+                    ],
+                    TextSize::Small,
+                    colors::RED,
+                );
+            }
+
+            if !clean_output {
+                draw_coin_counter(&levels, &player);
+            }
+
+            if editor_enabled && autosave.is_dirty() && !clean_output {
+                draw_unsaved_indicator();
+            }
+
+            if show_speedrun_timer && !clean_output {
+                draw_speedrun_timer(&speedrun_timer, &levels, &savegame);
+            }
+
+            // Respawning, which the full editor holds the respawn keys for resetting instead
+            let full_editor_enabled = editor.is_full() && editor_enabled;
+
+            if !modal_open && !full_editor_enabled && keybinds.is_pressed(Action::Respawn) {
+                player.respawn(&levels);
+            }
+
             // Check for resetting
-            if editor.is_full() && editor_enabled && input::is_key_down(KeyCode::R) {
+            if let Some((PendingAction::ResetLevels, _)) = confirmation {
+                reset_button_time = 5.0;
+            } else if full_editor_enabled
+                && !modal_open
+                && !capturing_keys
+                && keybinds.is_down(Action::Respawn)
+            {
                 reset_button_time += macroquad::time::get_frame_time();
 
                 if reset_button_time >= 5.0 {
                     reset_button_time = 5.0;
-
-                    if input::is_key_pressed(KeyCode::Enter) {
-                        fs::write(PATH_TO_LEVELS, include_str!("../original_levels.txt")).unwrap();
-
-                        break;
-                    }
+                    confirmation = Some((PendingAction::ResetLevels, MenuState::default()));
                 }
             } else if reset_button_time > 0.0 {
                 reset_button_time -= macroquad::time::get_frame_time() * 5.0;
@@ -291,7 +733,54 @@ async fn main() {
                 reset_button_time = 0.0;
             }
 
-            if reset_button_time > 0.0 {
+            if let Some((action, state)) = &mut confirmation {
+                match ui::confirmation(
+                    state,
+                    &keybinds,
+                    action.message(),
+                    window_width,
+                    window_height,
+                ) {
+                    // Quitting asks again if the level edits can't be saved
+                    Some(true) if matches!(action, PendingAction::SaveAndQuit) => {
+                        if autosave.save(&mut levels_file, levels.to_string()).is_ok() {
+                            return;
+                        }
+
+                        *action = PendingAction::QuitWithoutSaving;
+                        *state = MenuState::default();
+                    }
+                    Some(true) => {
+                        let written = match action {
+                            PendingAction::ResetLevels => {
+                                levels_file.write(ORIGINAL_LEVELS).map(|_| ())
+                            }
+                            PendingAction::RestoreBackup(backup) => restore_backup(
+                                backup,
+                                &mut levels_file,
+                                &mut save_file,
+                                &mut savegame,
+                            ),
+                            PendingAction::SaveAndQuit | PendingAction::QuitWithoutSaving => return,
+                        };
+
+                        // The levels are only reloaded if they were replaced
+                        match written {
+                            Ok(()) => break,
+                            Err(error) => {
+                                eprintln!("couldn't replace {LEVELS_FILE_NAME}: {error}");
+                                confirmation = None;
+                                reset_button_time = 0.0;
+                            }
+                        }
+                    }
+                    Some(false) => {
+                        confirmation = None;
+                        reset_button_time = 0.0;
+                    }
+                    None => (),
+                }
+            } else if reset_button_time > 0.0 {
                 shapes::draw_rectangle(
                     -LOGICAL_SCREEN_WIDTH / 2.0,
                     -window_height / 2.0,
@@ -303,128 +792,612 @@ async fn main() {
                     },
                 );
 
-                let message = if reset_button_time == 5.0 {
-                    "PRESS ENTER TO RESET"
-                } else {
-                    "RESETTING LEVEL FILE"
+                text::draw_centered_on_screen(
+                    "RESETTING LEVEL FILE",
+                    [window::screen_width() / 2.0, window::screen_height() / 2.0],
+                    TextSize::Large,
+                    Color {
+                        a: reset_button_time - 1.0,
+                        ..colors::BLACK
+                    },
+                );
+            }
+
+            if let Some(list) = &mut level_list {
+                ui::draw_backdrop(window_width, window_height);
+
+                let (changed, change) = match list.update(&levels, &keybinds) {
+                    Some(LevelListAction::Move { from, to }) => {
+                        (true, Some(levels.move_level(from, to)))
+                    }
+                    Some(LevelListAction::Duplicate(level_index)) => {
+                        (true, Some(levels.duplicate_level(level_index)))
+                    }
+                    Some(LevelListAction::Rename(level_index, name)) => {
+                        levels.metadata[level_index].name = name;
+                        (true, None)
+                    }
+                    Some(LevelListAction::Close) => {
+                        level_list = None;
+                        (false, None)
+                    }
+                    None => (false, None),
                 };
 
-                let (font_size, font_scale, font_scale_aspect) = text::camera_font_scale(1.0);
-
-                let TextDimensions {
-                    width,
-                    height,
-                    offset_y: _,
-                } = text::measure_text(message, None, font_size, font_scale);
-
-                text::draw_text_ex(
-                    message,
-                    -width / 2.0,
-                    -height / 2.0,
-                    TextParams {
-                        font_size,
-                        font_scale: -font_scale,
-                        font_scale_aspect: -font_scale_aspect,
-                        color: Color {
-                            a: reset_button_time - 1.0,
-                            ..colors::BLACK
-                        },
-                        ..Default::default()
-                    },
+                // The player's progress is kept by level and tile index, so it follows the levels
+                if let Some(change) = change {
+                    player.remap_levels(change);
+                    savegame.remap_levels(change);
+                    save_changed = true;
+                }
+
+                if changed {
+                    // Rearranging the levels is written straight away, along with any other
+                    // unsaved edits
+                    if let Err(error) = autosave.save(&mut levels_file, levels.to_string()) {
+                        eprintln!("couldn't save {LEVELS_FILE_NAME}: {error}");
+                    }
+                }
+            }
+
+            if paused && let Some(menu) = &mut controls_menu {
+                ui::draw_backdrop(window_width, window_height);
+
+                if menu.update(&mut keybinds, &mut keybinds_file) {
+                    controls_menu = None;
+                }
+            } else if paused {
+                ui::draw_backdrop(window_width, window_height);
+
+                let mut menu = Menu::begin(&mut pause_menu, &keybinds);
+
+                if menu.button("RESUME") {
+                    paused = false;
+                }
+
+                if menu.button("RESTART LEVEL") {
+                    editor.restart_level(&mut levels, &mut player);
+                    level_updates = 0;
+                    level_used_debug_flags = false;
+                    paused = false;
+                }
+
+                if menu.toggle("FULLSCREEN", &mut fullscreen) {
+                    window::set_fullscreen(fullscreen);
+                }
+
+                if menu.toggle("KEEP MOMENTUM", &mut keep_velocity_on_inversion) {
+                    player.keep_velocity_on_inversion = keep_velocity_on_inversion;
+                }
+
+                menu.toggle("CLEAN OUTPUT", &mut clean_output);
+                menu.toggle("SPEEDRUN TIMER", &mut show_speedrun_timer);
+
+                menu.slider("GAME SPEED", &mut game_speed, 0.5..=1.0, 0.125);
+
+                if menu.button("MAIN MENU") {
+                    paused = false;
+                    screen = Screen::title();
+                }
+
+                if menu.button("CONTROLS") {
+                    controls_menu = Some(ControlsMenu::default());
+                }
+
+                if let Some(backup) = &latest_backup
+                    && menu.button("RESTORE BACKUP")
+                {
+                    paused = false;
+                    confirmation = Some((
+                        PendingAction::RestoreBackup(backup.clone()),
+                        MenuState::default(),
+                    ));
+                }
+
+                if menu.button("QUIT") {
+                    quitting = true;
+                }
+
+                menu.end();
+            }
+
+            if !screen.is_playing() {
+                ui::draw_backdrop(window_width, window_height);
+
+                match screen.update(&levels, &savegame, &keybinds, &camera) {
+                    Some(ScreenAction::Continue) => screen = Screen::Playing,
+                    Some(ScreenAction::PlayLevel(level_index)) => {
+                        editor.force_undo_temporary_actions(&mut levels);
+                        player.enter_level(&mut levels, level_index);
+                        level_updates = 0;
+                        level_used_debug_flags = false;
+                        speedrun_timer = SpeedrunTimer::default();
+                        screen = Screen::Playing;
+                    }
+                    Some(ScreenAction::Quit) => quitting = true,
+                    None => {}
+                }
+            }
+
+            if idle && screen.is_playing() && !paused {
+                ui::draw_backdrop(window_width, window_height);
+
+                text::draw_centered("IDLE", [0.0, 0.5], TextSize::Large, colors::WHITE);
+                text::draw_centered(
+                    "PRESS ANY KEY TO RESUME",
+                    [0.0, -0.5],
+                    TextSize::Small,
+                    colors::WHITE,
                 );
             }
 
+            if console.open {
+                console.draw(window_width, window_height);
+            }
+
+            if !clean_output {
+                debug_overlay.draw();
+            }
+
+            #[cfg(feature = "profile")]
+            if profiler.show_flamegraph {
+                profiler.draw_flamegraph();
+            }
+
+            #[cfg(feature = "status-file")]
+            status_file.update(
+                if modal_open {
+                    status::Activity::Paused
+                } else if editor_enabled {
+                    status::Activity::Editing
+                } else {
+                    status::Activity::Playing
+                },
+                levels.level_index,
+                levels.num_levels,
+                player.deaths,
+            );
+
+            // Saving is best effort, so the game keeps going if the file can't be written
+            if save_changed {
+                let _ = savegame.save(&mut save_file);
+            }
+
+            // The player is asked before quitting with unsaved level edits, which are written on
+            // the way out, and only lost if they can't be and the player confirms quitting anyway
+            if quitting {
+                if !autosave.is_dirty() {
+                    return;
+                }
+
+                if !matches!(
+                    confirmation,
+                    Some((
+                        PendingAction::SaveAndQuit | PendingAction::QuitWithoutSaving,
+                        _
+                    ))
+                ) {
+                    paused = false;
+                    confirmation = Some((PendingAction::SaveAndQuit, MenuState::default()));
+                }
+            }
+
             window::next_frame().await;
         }
     }
 }
 
+/// A destructive action waiting for the player to confirm it
 #[derive(Clone, Debug)]
-pub enum Editor {
-    Limited { last_selected: Option<usize> },
-    Full,
+pub enum PendingAction {
+    ResetLevels,
+    RestoreBackup(PathBuf),
+    /// Quitting with unsaved level edits, which are saved first
+    SaveAndQuit,
+    /// Quitting when the level edits couldn't be saved
+    QuitWithoutSaving,
 }
 
-impl Editor {
-    /// Returns whether or not to write the changes made
+impl PendingAction {
     #[must_use]
-    pub fn toggle_tile_index(
-        &mut self,
-        tile_index: usize,
-        levels: &mut Levels,
-        player: &mut Player,
-    ) -> bool {
-        for gem in [levels.limited_gem, levels.full_gem] {
-            if let Some(gem_index) = gem {
-                if tile_index == gem_index || tile_index == gem_index - 1 {
-                    return false;
-                }
-            }
+    pub fn message(&self) -> &'static str {
+        match self {
+            PendingAction::ResetLevels => "RESET TO THE ORIGINAL LEVELS?",
+            PendingAction::RestoreBackup(_) => "RESTORE THE LATEST BACKUP?",
+            PendingAction::SaveAndQuit => "SAVE THE LEVEL EDITS AND QUIT?",
+            PendingAction::QuitWithoutSaving => "QUIT WITHOUT SAVING THE LEVELS?",
         }
+    }
+}
+
+/// Reports a file which couldn't be loaded, and keeps it as a `.corrupt` file so the defaults used
+/// instead don't overwrite it
+fn set_aside_unloadable(file: &mut TrackedFile, error: &io::Error) {
+    eprintln!("couldn't load {}: {error}", file.path.display());
 
-        if let Editor::Limited { .. } = self {
-            if levels.level_index == levels.num_levels - 1 || tile_index < Levels::LEVEL_HEIGHT {
-                return false;
+    match file.set_aside_corrupt() {
+        Ok(corrupt_path) => eprintln!("kept it as {}", corrupt_path.display()),
+        Err(error) => eprintln!("couldn't keep it as a .corrupt file: {error}"),
+    }
+}
+
+/// Replaces the levels and the save with the ones in a version change backup. The player's
+/// progress is kept by level, so the save is always restored along with the levels, starting over
+/// if the backup doesn't have one. Only failing to restore the levels is an error, since the
+/// restored save is kept in `savegame` either way.
+fn restore_backup(
+    backup: &Path,
+    levels_file: &mut TrackedFile,
+    save_file: &mut TrackedFile,
+    savegame: &mut SaveGame,
+) -> io::Result<()> {
+    let backup_levels = fs::read_to_string(backup.join(LEVELS_FILE_NAME))?;
+    let mut backup_savegame = SaveGame::load(&mut TrackedFile {
+        path: backup.join(SAVE_FILE_NAME),
+        last_modified: None,
+    })?;
+    // The backup's release notes have already been seen
+    backup_savegame.seen_version = savegame.seen_version.clone();
+
+    levels_file.write(backup_levels)?;
+    *savegame = backup_savegame;
+
+    if let Err(error) = savegame.save(save_file) {
+        eprintln!("couldn't save {SAVE_FILE_NAME}: {error}");
+    }
+
+    Ok(())
+}
+
+/// Reads the levels file, creating it from the original levels if it doesn't exist yet
+fn read_levels(levels_file: &mut TrackedFile) -> String {
+    match levels_file.read() {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            // The game can still be played without the file, and saving tries to create it again
+            if let Err(error) = levels_file.write(ORIGINAL_LEVELS) {
+                eprintln!("couldn't create {LEVELS_FILE_NAME}: {error}");
             }
-        }
 
-        levels.tiles[tile_index] ^= true;
+            ORIGINAL_LEVELS.to_owned()
+        }
+        Err(error) => panic!("{error}"),
+    }
+}
 
-        if player.is_intersecting(levels) {
-            levels.tiles[tile_index] ^= true;
-            return false;
+/// Triggers the gems the player touches in an update, which toggle the editors they give. Returns
+/// `true` if the save changed.
+fn update_gems(
+    levels: &mut Levels,
+    player: &Player,
+    editor: &mut Editor,
+    editor_enabled: &mut bool,
+    savegame: &mut SaveGame,
+    gem_triggers: &mut [PickupTrigger; 2],
+) -> bool {
+    let mut save_changed = false;
+
+    let gems = [(levels.limited_gem, false), (levels.full_gem, true)];
+
+    for ((gem, is_full_gem), trigger) in gems.into_iter().zip(gem_triggers) {
+        let Some(gem_index) = gem else {
+            continue;
+        };
+
+        let enabled = *editor_enabled && (!is_full_gem || editor.is_full());
+
+        let Some(position) = levels.gem_center(gem_index, enabled) else {
+            continue;
+        };
+
+        if !trigger.update(
+            player.touches_pickup(Pickup::Gem, position),
+            1.0 / Player::UPDATES_PER_SECOND,
+        ) {
+            continue;
         }
 
-        match self {
-            Editor::Limited { last_selected } => {
-                if *last_selected == Some(tile_index) {
-                    *last_selected = None;
-                } else if let Some(last_selected) = last_selected {
-                    levels.tiles[*last_selected] ^= true;
-
-                    if player.is_intersecting(levels) {
-                        levels.tiles[tile_index] ^= true;
-                        levels.tiles[*last_selected] ^= true;
-                        return false;
-                    }
+        let collected = match is_full_gem {
+            true => &mut savegame.full_gem_collected,
+            false => &mut savegame.limited_gem_collected,
+        };
 
-                    *last_selected = tile_index;
-                } else {
-                    *last_selected = Some(tile_index);
-                }
+        save_changed |= !*collected;
+        *collected = true;
 
-                false
+        if is_full_gem {
+            if enabled {
+                *editor = Editor::Limited {
+                    last_selected: None,
+                };
+            } else {
+                *editor_enabled = true;
+
+                editor.force_undo_temporary_actions(levels);
+                *editor = Editor::Full { drag: None };
             }
-            Editor::Full => true,
-        }
-    }
+        } else if enabled {
+            *editor_enabled = false;
 
-    pub fn force_undo_temporary_actions(&mut self, levels: &mut Levels) {
-        match self {
-            Editor::Limited { last_selected } => {
-                if let Some(tile_index) = *last_selected {
-                    levels.tiles[tile_index] ^= true;
-                    *last_selected = None;
-                }
+            if !editor.is_limited() {
+                *editor = Editor::Limited {
+                    last_selected: None,
+                };
             }
-            Editor::Full => {}
+        } else {
+            *editor_enabled = true;
         }
     }
 
-    /// Returns `true` if the editor is [`Full`].
-    ///
-    /// [`Full`]: Editor::Full
-    #[must_use]
-    pub fn is_full(&self) -> bool {
-        matches!(self, Self::Full)
+    save_changed
+}
+
+/// The position of the tile under the mouse, if it's over the level
+fn mouse_tile_position(camera: &Camera2D, levels: &Levels) -> Option<[usize; 2]> {
+    let mouse_position = <[f32; 2]>::from(camera.screen_to_world(input::mouse_position().into()));
+
+    let mouse_position = [
+        mouse_position[0] + LOGICAL_SCREEN_WIDTH / 2.0,
+        mouse_position[1] + LOGICAL_SCREEN_HEIGHT / 2.0,
+    ];
+
+    levels.index_of_position(mouse_position).ok()
+}
+
+/// The index of the tile a gem would rest in if it were shown in the tile under the mouse, which is
+/// where gems sink to while the full editor is enabled
+fn mouse_gem_index(camera: &Camera2D, levels: &Levels) -> Option<usize> {
+    let [x, y] = mouse_tile_position(camera, levels)?;
+
+    if y + 1 < Levels::LEVEL_HEIGHT {
+        levels.index_of([x, y + 1])
+    } else {
+        None
     }
+}
 
-    /// Returns `true` if the editor is [`Limited`].
-    ///
-    /// [`Limited`]: Editor::Limited
-    #[must_use]
-    pub fn is_limited(&self) -> bool {
-        matches!(self, Self::Limited { .. })
+/// Outlines where a gem would be placed, in red if it can't go there
+fn draw_gem_preview(gem_index: usize, levels: &Levels, player: &Player) {
+    let Some(position) = levels.gem_center(gem_index, true) else {
+        return;
+    };
+
+    let color = if editor::can_place_gem(gem_index, levels, player)
+        || [levels.limited_gem, levels.full_gem].contains(&Some(gem_index))
+    {
+        colors::WHITE
+    } else {
+        colors::RED
+    };
+
+    shapes::draw_circle_lines(
+        position[0] - LOGICAL_SCREEN_WIDTH / 2.0,
+        position[1] - LOGICAL_SCREEN_HEIGHT / 2.0,
+        player.physics.pickup_radius(Pickup::Gem),
+        0.0625,
+        color,
+    );
+}
+
+/// Returns `true` if any key or mouse button is held down, or the mouse has moved
+fn has_any_input() -> bool {
+    !input::get_keys_down().is_empty()
+        || [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+            .into_iter()
+            .any(input::is_mouse_button_down)
+        || input::mouse_delta_position() != Vec2::ZERO
+}
+
+/// Shades the tiles a drag in the full editor will change, in the color they will become
+fn draw_drag_preview(drag: &Drag, levels: &Levels) {
+    let tile = levels
+        .get(drag.start)
+        .map_or(Tile::Solid, |tile| tile.toggled());
+
+    for position in drag.positions() {
+        draw_preview_tile(position, tile);
+    }
+}
+
+/// Draws a translucent tile which hasn't been placed yet, at a position on the screen like the
+/// indices of [`Levels`]
+fn draw_preview_tile(position: [usize; 2], tile: Tile) {
+    let color = if tile.is_solid() {
+        colors::BLACK
+    } else {
+        colors::WHITE
+    };
+
+    let x = position[0] as f32 - LOGICAL_SCREEN_WIDTH / 2.0;
+    let y = position[1] as f32 - LOGICAL_SCREEN_HEIGHT / 2.0;
+
+    shapes::draw_rectangle(x, y, 1.0, 1.0, Color { a: 0.5, ..color });
+    shapes::draw_rectangle_lines(x, y, 1.0, 1.0, 0.0625, colors::GRAY);
+}
+
+/// Outlines the tiles selected for copying
+fn draw_selection(selection: &Drag) {
+    let [left, bottom] = [0, 1].map(|i| selection.start[i].min(selection.end[i]) as f32);
+    let [width, height] =
+        [0, 1].map(|i| selection.start[i].abs_diff(selection.end[i]) as f32 + 1.0);
+
+    let x = left - LOGICAL_SCREEN_WIDTH / 2.0;
+    let y = bottom - LOGICAL_SCREEN_HEIGHT / 2.0;
+
+    shapes::draw_rectangle(
+        x,
+        y,
+        width,
+        height,
+        Color {
+            a: 0.25,
+            ..colors::GRAY
+        },
+    );
+    shapes::draw_rectangle_lines(x, y, width, height, 0.125, colors::GRAY);
+}
+
+/// Draws the speedrun time in the top left corner, with how far ahead of the best split it is in
+/// green or behind in red below it
+fn draw_speedrun_timer(timer: &SpeedrunTimer, levels: &Levels, savegame: &SaveGame) {
+    let left = -LOGICAL_SCREEN_WIDTH / 2.0;
+    let top = LOGICAL_SCREEN_HEIGHT / 2.0;
+
+    shapes::draw_rectangle(
+        left,
+        top - 1.25,
+        3.5,
+        1.25,
+        Color {
+            a: 0.75,
+            ..colors::BLACK
+        },
+    );
+
+    let time = speedrun::format_time(timer.time());
+    let time = if timer.is_finished(levels) {
+        format!("FINAL {time}")
+    } else {
+        time
+    };
+
+    text::draw_left_aligned(
+        &time,
+        [left + 0.25, top - 0.375],
+        TextSize::Small,
+        colors::WHITE,
+    );
+
+    if let Some(delta) = timer.delta(levels, savegame) {
+        let (sign, color) = if delta <= 0.0 {
+            ('-', colors::GREEN)
+        } else {
+            ('+', colors::RED)
+        };
+
+        text::draw_left_aligned(
+            &format!("{sign}{}", speedrun::format_time(delta)),
+            [left + 0.25, top - 0.875],
+            TextSize::Small,
+            color,
+        );
+    }
+}
+
+/// Draws a note in the bottom right corner while there are level edits which haven't been saved
+fn draw_unsaved_indicator() {
+    let label = "UNSAVED";
+    let [width, _] = text::measure(label, TextSize::Small);
+
+    let right = LOGICAL_SCREEN_WIDTH / 2.0;
+    let bottom = -LOGICAL_SCREEN_HEIGHT / 2.0;
+
+    shapes::draw_rectangle(
+        right - width - 0.5,
+        bottom,
+        width + 0.5,
+        0.75,
+        Color {
+            a: 0.75,
+            ..colors::BLACK
+        },
+    );
+
+    text::draw_left_aligned(
+        label,
+        [right - width - 0.25, bottom + 0.375],
+        TextSize::Small,
+        colors::WHITE,
+    );
+}
+
+/// Draws how many of the current level's coins have been collected at the top of the level, if
+/// it has any
+fn draw_coin_counter(levels: &Levels, player: &Player) {
+    let coins = levels.coins(levels.level_index).collect::<Vec<_>>();
+
+    if coins.is_empty() {
+        return;
+    }
+
+    let collected = coins
+        .iter()
+        .filter(|tile_index| player.coins.contains(tile_index))
+        .count();
+
+    let top = LOGICAL_SCREEN_HEIGHT / 2.0;
+
+    shapes::draw_rectangle(
+        -1.25,
+        top - 0.75,
+        2.5,
+        0.75,
+        Color {
+            a: 0.75,
+            ..colors::BLACK
+        },
+    );
+
+    text::draw_centered(
+        &format!("o {collected}/{}", coins.len()),
+        [0.0, top - 0.375],
+        TextSize::Small,
+        colors::WHITE,
+    );
+}
+
+/// Draws a tile over the black background, with the bottom left corner at `position`. Switches
+/// lean the other way once their level's switch region is flipped.
+fn draw_tile(tile: Tile, position: [f32; 2], switched: bool) {
+    let [x, y] = position;
+
+    match tile {
+        Tile::Empty => shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE),
+        Tile::Solid => {}
+        Tile::Spike => {
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+            shapes::draw_triangle(
+                [x, y].into(),
+                [x + 1.0, y].into(),
+                [x + 0.5, y + 1.0].into(),
+                colors::BLACK,
+            );
+        }
+        Tile::OneWay => {
+            shapes::draw_rectangle(x, y, 1.0, 0.75, colors::WHITE);
+        }
+        Tile::Checkpoint => {
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+            shapes::draw_rectangle_lines(x + 0.25, y + 0.25, 0.5, 0.5, 0.125, colors::BLACK);
+        }
+        Tile::Curse => {
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+            shapes::draw_line(x + 0.25, y + 0.25, x + 0.75, y + 0.75, 0.125, colors::BLACK);
+            shapes::draw_line(x + 0.25, y + 0.75, x + 0.75, y + 0.25, 0.125, colors::BLACK);
+        }
+        Tile::Switch => {
+            let lean = if switched { 0.25 } else { -0.25 };
+
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+            shapes::draw_rectangle(x + 0.25, y, 0.5, 0.125, colors::BLACK);
+            shapes::draw_line(x + 0.5, y, x + 0.5 + lean, y + 0.625, 0.125, colors::BLACK);
+        }
+        Tile::Coin => {
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+            shapes::draw_circle(x + 0.5, y + 0.5, 0.1875, colors::BLACK);
+        }
+        Tile::DoubleJump => {
+            shapes::draw_rectangle(x, y, 1.0, 1.0, colors::WHITE);
+
+            for bottom in [0.25, 0.5] {
+                shapes::draw_triangle(
+                    [x + 0.25, y + bottom].into(),
+                    [x + 0.75, y + bottom].into(),
+                    [x + 0.5, y + bottom + 0.25].into(),
+                    colors::BLACK,
+                );
+            }
+        }
     }
 }
 
@@ -0,0 +1,96 @@
+use crate::pickup::Pickup;
+use crate::player::Player;
+
+/// The numbers which decide how the player moves. Levels can scale some of these with a
+/// [`PhysicsOverrides`] in their metadata.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsConfig {
+    /// The speed gained towards the ground each update, in tiles per update
+    pub gravity: f32,
+    /// The fraction of horizontal speed lost each update
+    pub friction: f32,
+    /// The horizontal speed gained each update while moving, in tiles per update
+    pub acceleration: f32,
+    /// The speed away from the ground at the start of a jump, in tiles per update
+    pub jump_impulse: f32,
+    /// How close the player's center has to get to the center of each kind of [`Pickup`] to pick
+    /// it up, in tiles, in the order of [`Pickup::ALL`]
+    pub pickup_radii: [f32; 3],
+}
+
+/// Multipliers for a level's [`PhysicsConfig`], and pickup radii to use instead of its own, which
+/// only apply while the player is in that level
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PhysicsOverrides {
+    pub gravity_scale: Option<f32>,
+    pub friction_scale: Option<f32>,
+    pub jump_scale: Option<f32>,
+    /// In the order of [`Pickup::ALL`]
+    pub pickup_radii: [Option<f32>; 3],
+}
+
+impl PhysicsConfig {
+    pub const DEFAULT: Self = Self {
+        gravity: 1.0 / 32.0 / Player::UPS_SCALE / Player::UPS_SCALE,
+        friction: 0.2 / Player::UPS_SCALE,
+        acceleration: 1.0 / 32.0 / Player::UPS_SCALE / Player::UPS_SCALE,
+        jump_impulse: 7.5 / 32.0 / Player::UPS_SCALE,
+        // Coins and double jumps are picked up once the player's center is about inside their tile
+        pickup_radii: [Player::SIZE, 0.5, 0.5],
+    };
+
+    #[must_use]
+    pub fn with_overrides(self, overrides: &PhysicsOverrides) -> Self {
+        Self {
+            gravity: self.gravity * overrides.gravity_scale.unwrap_or(1.0),
+            friction: (self.friction * overrides.friction_scale.unwrap_or(1.0)).clamp(0.0, 1.0),
+            acceleration: self.acceleration,
+            jump_impulse: self.jump_impulse * overrides.jump_scale.unwrap_or(1.0),
+            pickup_radii: Pickup::ALL.map(|pickup| {
+                overrides.pickup_radii[pickup.index()].unwrap_or(self.pickup_radius(pickup))
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn pickup_radius(&self, pickup: Pickup) -> f32 {
+        self.pickup_radii[pickup.index()]
+    }
+}
+
+impl PhysicsOverrides {
+    /// Returns `true` if the scale can be used for one of the multipliers, which means it's
+    /// finite and positive
+    #[must_use]
+    pub fn is_valid_scale(scale: f32) -> bool {
+        scale.is_finite() && scale > 0.0
+    }
+
+    /// Returns `true` if the radius can be used for a pickup, which means it isn't negative or NaN
+    #[must_use]
+    pub fn is_valid_pickup_radius(radius: f32) -> bool {
+        radius >= 0.0
+    }
+
+    /// Returns `true` if every scale and radius which is set can be used
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        [self.gravity_scale, self.friction_scale, self.jump_scale]
+            .into_iter()
+            .flatten()
+            .all(Self::is_valid_scale)
+            && self
+                .pickup_radii
+                .into_iter()
+                .flatten()
+                .all(Self::is_valid_pickup_radius)
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,63 @@
+/// Something the player picks up by getting close enough to its center
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pickup {
+    Gem,
+    Coin,
+    DoubleJump,
+}
+
+/// Decides when touching something like a gem should do anything. Touching it triggers it once,
+/// and it can only be triggered again once the player has let go of it and the cooldown has run
+/// out, so standing on it or brushing its edge doesn't set it off over and over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PickupTrigger {
+    /// Whether the player was touching it the last time it was updated
+    touching: bool,
+    /// The time in seconds until it can be triggered again
+    cooldown: f32,
+}
+
+impl Pickup {
+    pub const ALL: [Pickup; 3] = [Pickup::Gem, Pickup::Coin, Pickup::DoubleJump];
+
+    /// The name of the pickup in the levels file, where its radius is set with `#<name>_radius`
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Pickup::Gem => "gem",
+            Pickup::Coin => "coin",
+            Pickup::DoubleJump => "double_jump",
+        }
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl PickupTrigger {
+    /// How long after being triggered it can't be triggered again
+    pub const COOLDOWN: f32 = 0.5;
+
+    /// Updates whether the player is touching it, `seconds` after the last update, returning
+    /// whether this touch triggers it. Touches which start during the cooldown never trigger it,
+    /// even once the cooldown runs out.
+    pub fn update(&mut self, touching: bool, seconds: f32) -> bool {
+        self.cooldown = (self.cooldown - seconds).max(0.0);
+
+        let triggered = touching && !self.touching && self.cooldown == 0.0;
+
+        if triggered {
+            self.cooldown = Self::COOLDOWN;
+        }
+
+        self.touching = touching;
+
+        triggered
+    }
+
+    #[must_use]
+    pub fn is_cooling_down(&self) -> bool {
+        self.cooldown > 0.0
+    }
+}
@@ -1,16 +1,26 @@
-use std::{array, sync::LazyLock};
-
-use macroquad::input::{self, KeyCode};
-
-use crate::level::Levels;
+use std::{
+    array,
+    collections::{BTreeMap, BTreeSet},
+    hash::{Hash, Hasher},
+};
+
+use crate::debug_flags::DebugFlags;
+use crate::keybinds::{Action, Keybinds};
+use crate::level::{LevelChange, Levels};
+use crate::physics::PhysicsConfig;
+use crate::pickup::Pickup;
+use crate::tile::Tile;
 
 const UP: usize = 0;
 const LEFT: usize = 1;
 const DOWN: usize = 2;
 const RIGHT: usize = 3;
 
+#[derive(Clone, Debug)]
 pub struct Player {
     pub position: [f32; 2],
+    /// Where the player was before the last update, which they are drawn moving from
+    pub previous_position: [f32; 2],
     pub velocity: [f32; 2],
     pub air_kind: bool,
     pub on_ground: bool,
@@ -18,6 +28,37 @@ pub struct Player {
     pub inputs_down: [bool; 4],
     pub inputs_ready: [bool; 4],
     pub keep_velocity_on_inversion: bool,
+    /// Where the player entered the current level
+    pub spawn_point: SpawnPoint,
+    /// The last checkpoint the player passed through in each level, by level index. These are
+    /// kept for the rest of the session, even after leaving the level.
+    pub checkpoints: BTreeMap<usize, SpawnPoint>,
+    /// The number of times the player has died
+    pub deaths: u32,
+    /// The coins the player has collected, by tile index
+    pub coins: BTreeSet<usize>,
+    /// The number of updates left in the death animation, after which the player respawns
+    pub death_time: u8,
+    /// The number of updates left with mirrored horizontal controls, from passing through a curse
+    pub curse_time: u16,
+    /// The number of jumps the player can make in the air between landings, from passing through
+    /// a double jump
+    pub air_jumps: u8,
+    /// The number of jumps the player has left before they land again
+    pub jumps_remaining: u8,
+    /// The physics of the level the player is in, which is refreshed every update
+    pub physics: PhysicsConfig,
+    /// The developer options which affect the player, which are kept in the save
+    pub debug_flags: DebugFlags,
+    /// How many tiles per second the player flies with noclip
+    pub fly_speed: f32,
+}
+
+/// A state the player can be sent back to by [`Player::respawn`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnPoint {
+    pub position: [f32; 2],
+    pub air_kind: bool,
 }
 
 impl Player {
@@ -25,18 +66,33 @@ impl Player {
     pub const UPS_SCALE: f32 = Self::UPDATES_PER_SECOND / 30.0;
 
     pub const SIZE: f32 = 0.5;
-    pub const GRAVITY: f32 = 1.0 / 32.0 / Self::UPS_SCALE / Self::UPS_SCALE;
 
     pub const MAXIMUM_UPDATES_PER_FRAME: usize = 5;
 
+    /// How far the player can be nudged sideways to get around the corner of a tile they clipped
+    /// while jumping, in tiles
+    pub const CORNER_CORRECTION: f32 = 0.125;
+
+    /// The furthest the player moves along either axis before checking for collisions again, which
+    /// is short enough that they can't pass through a tile without touching it
+    pub const MAXIMUM_STEP: f32 = Self::SIZE / 2.0;
+
     pub const CYOTE_FRAMES: u8 = (0.05 * Self::UPDATES_PER_SECOND) as u8;
+    pub const DEATH_FRAMES: u8 = (0.5 * Self::UPDATES_PER_SECOND) as u8;
+    pub const CURSE_FRAMES: u16 = (5.0 * Self::UPDATES_PER_SECOND) as u16;
+
+    /// How many tiles per second the player flies with noclip, unless it's changed
+    pub const DEFAULT_FLY_SPEED: f32 = 8.0;
 
     pub fn new(keep_velocity_on_inversion: bool) -> Self {
+        let position = [
+            crate::LOGICAL_SCREEN_WIDTH / 2.0,
+            crate::LOGICAL_SCREEN_HEIGHT / 2.0,
+        ];
+
         Self {
-            position: [
-                crate::LOGICAL_SCREEN_WIDTH / 2.0,
-                crate::LOGICAL_SCREEN_HEIGHT / 2.0,
-            ],
+            position,
+            previous_position: position,
             velocity: [0.0, 0.0],
             air_kind: false,
             on_ground: false,
@@ -44,48 +100,120 @@ impl Player {
             inputs_down: [false; 4],
             inputs_ready: [false; 4],
             keep_velocity_on_inversion,
+            spawn_point: SpawnPoint {
+                position,
+                air_kind: false,
+            },
+            checkpoints: BTreeMap::new(),
+            deaths: 0,
+            coins: BTreeSet::new(),
+            death_time: 0,
+            curse_time: 0,
+            air_jumps: 0,
+            jumps_remaining: 0,
+            physics: PhysicsConfig::DEFAULT,
+            debug_flags: DebugFlags::default(),
+            fly_speed: Self::DEFAULT_FLY_SPEED,
         }
     }
 
-    pub fn update_input(&mut self) {
-        static KEYBINDS: LazyLock<[Vec<KeyCode>; 4]> = LazyLock::new(|| {
-            [
-                vec![KeyCode::W, KeyCode::Up, KeyCode::Space],
-                vec![KeyCode::A, KeyCode::Left],
-                vec![KeyCode::S, KeyCode::Down],
-                vec![KeyCode::D, KeyCode::Right],
-            ]
-        });
+    pub fn update_input(&mut self, keybinds: &Keybinds) {
+        let movement = [Action::Jump, Action::Left, Action::Invert, Action::Right];
 
-        fn is_down(keys: &[KeyCode]) -> bool {
-            keys.iter().any(|key| input::is_key_down(*key))
-        }
-
-        fn is_pressed(keys: &[KeyCode]) -> bool {
-            keys.iter().any(|key| input::is_key_pressed(*key))
-        }
+        self.add_input(
+            movement.map(|action| keybinds.is_down(action)),
+            movement.map(|action| keybinds.is_pressed(action)),
+        );
+    }
 
-        self.inputs_down = array::from_fn(|i| self.inputs_down[i] || is_down(&KEYBINDS[i]));
-        self.inputs_ready = array::from_fn(|i| {
-            (self.inputs_ready[i] || is_pressed(&KEYBINDS[i])) && self.inputs_down[i]
-        });
+    /// Adds to the input for the next update, given which movement inputs are held down and which
+    /// were pressed since the last frame. The inputs are in the same order as [`Self::inputs_down`].
+    pub fn add_input(&mut self, down: [bool; 4], pressed: [bool; 4]) {
+        self.inputs_down = array::from_fn(|i| self.inputs_down[i] || down[i]);
+        self.inputs_ready =
+            array::from_fn(|i| (self.inputs_ready[i] || pressed[i]) && self.inputs_down[i]);
     }
 
     pub fn update(&mut self, levels: &mut Levels) {
-        self.velocity[1] += self.gravity();
+        self.physics = levels.physics();
+        self.previous_position = self.position;
 
-        let Some(x_collision) = self.move_by(levels, [self.velocity[0], 0.0]) else {
-            if self.position[0] > crate::LOGICAL_SCREEN_WIDTH / 2.0 {
-                levels.next_level();
-                self.position[0] = Self::SIZE / 2.0;
-            } else {
-                levels.previous_level();
-                self.position[0] = crate::LOGICAL_SCREEN_WIDTH - Self::SIZE / 2.0;
+        if self.death_time > 0 {
+            self.death_time -= 1;
+
+            if self.death_time == 0 {
+                self.respawn(levels);
+
+                // The level may have changed over the spawn point while the player was dead
+                self.resolve_crush(levels);
             }
 
+            self.inputs_down = [false; 4];
+            return;
+        }
+
+        if self.debug_flags.noclip {
+            self.fly(levels);
+            return;
+        }
+
+        self.velocity[1] += self.gravity();
+
+        let Some(x_collision) = self.move_by(levels, [self.velocity[0], 0.0]) else {
+            self.leave_level(levels);
             return;
         };
-        let y_collision = self.move_by(levels, [0.0, self.velocity[1]]).unwrap();
+        let position_before_y = self.position;
+        let mut y_collision = self.move_by(levels, [0.0, self.velocity[1]]).unwrap();
+
+        if y_collision && self.velocity[1] * self.gravity() < 0.0 {
+            y_collision = !self.correct_corner(levels, position_before_y);
+        }
+
+        if !self.debug_flags.invincible && self.is_touching(levels, Tile::Spike) {
+            self.die();
+            return;
+        }
+
+        if self.curse_time > 0 {
+            self.curse_time -= 1;
+        }
+
+        if levels.get_from_position(self.position) == Some(Tile::Curse) {
+            self.curse_time = Self::CURSE_FRAMES;
+        }
+
+        // Switches flip when the player passes into them
+        if levels.get_from_position(self.position) == Some(Tile::Switch)
+            && levels.get_from_position(self.previous_position) != Some(Tile::Switch)
+        {
+            levels.flip_switch(levels.level_index);
+
+            if self.resolve_crush(levels) && self.is_dead() {
+                return;
+            }
+        }
+
+        let coins = self.touched_pickup_tiles(levels, Tile::Coin, Pickup::Coin);
+        self.coins.extend(coins);
+
+        if !self
+            .touched_pickup_tiles(levels, Tile::DoubleJump, Pickup::DoubleJump)
+            .is_empty()
+        {
+            self.air_jumps = 1;
+            self.jumps_remaining = self.air_jumps;
+        }
+
+        if levels.get_from_position(self.position) == Some(Tile::Checkpoint) {
+            self.checkpoints.insert(
+                levels.level_index,
+                SpawnPoint {
+                    position: self.position.map(|x| x.floor() + 0.5),
+                    air_kind: self.air_kind,
+                },
+            );
+        }
 
         if x_collision {
             self.velocity[0] = 0.0;
@@ -101,6 +229,7 @@ impl Player {
             if self.velocity[1] * self.gravity() > 0.0 {
                 self.on_ground = true;
                 self.cyote_time = Self::CYOTE_FRAMES;
+                self.jumps_remaining = self.air_jumps;
             }
 
             let impact_velocity = self.velocity[1];
@@ -119,13 +248,22 @@ impl Player {
         if self.inputs_ready[UP] && (self.cyote_time > 0 || self.on_ground) {
             self.inputs_ready[UP] = false;
 
-            self.velocity[1] = -7.5 * Self::UPS_SCALE * self.gravity();
+            self.velocity[1] = -self.physics.jump_impulse * self.gravity().signum();
+        } else if self.inputs_ready[UP] && self.jumps_remaining > 0 {
+            self.inputs_ready[UP] = false;
+            self.jumps_remaining -= 1;
+
+            self.velocity[1] = -self.physics.jump_impulse * self.gravity().signum();
         }
 
-        let x_input = self.inputs_down[RIGHT] as isize - self.inputs_down[LEFT] as isize;
+        let mut x_input = self.inputs_down[RIGHT] as isize - self.inputs_down[LEFT] as isize;
 
-        self.velocity[0] *= 1.0 - 0.2 / Self::UPS_SCALE;
-        self.velocity[0] += x_input as f32 / 32.0 / Self::UPS_SCALE / Self::UPS_SCALE;
+        if self.is_cursed() {
+            x_input = -x_input;
+        }
+
+        self.velocity[0] *= 1.0 - self.physics.friction;
+        self.velocity[0] += x_input as f32 * self.physics.acceleration;
 
         if self.on_ground && self.inputs_ready[DOWN] {
             let old_position = self.position;
@@ -152,21 +290,281 @@ impl Player {
         self.inputs_down = [false; 4];
     }
 
+    /// Where to draw the player, a fraction of the way through the next update from where they
+    /// were to where they are now
+    #[must_use]
+    pub fn interpolated_position(&self, fraction: f32) -> [f32; 2] {
+        array::from_fn(|i| {
+            self.previous_position[i] + (self.position[i] - self.previous_position[i]) * fraction
+        })
+    }
+
+    /// Moves the player straight in the direction of their inputs, ignoring tiles
+    fn fly(&mut self, levels: &mut Levels) {
+        let direction = [
+            self.inputs_down[RIGHT] as isize - self.inputs_down[LEFT] as isize,
+            self.inputs_down[UP] as isize - self.inputs_down[DOWN] as isize,
+        ];
+
+        let speed = self.fly_speed / Self::UPDATES_PER_SECOND;
+
+        self.velocity = direction.map(|direction| direction as f32 * speed);
+        self.position[0] += self.velocity[0];
+        self.position[1] = (self.position[1] + self.velocity[1]).clamp(
+            Self::SIZE / 2.0,
+            crate::LOGICAL_SCREEN_HEIGHT - Self::SIZE / 2.0,
+        );
+
+        if !(0.0..crate::LOGICAL_SCREEN_WIDTH).contains(&self.position[0]) {
+            self.leave_level(levels);
+        }
+
+        self.inputs_down = [false; 4];
+    }
+
+    /// Tries the vertical move of a jump which hit a tile again from slightly to either side, so
+    /// that clipping the corner of a tile doesn't stop the jump. Returns `true` and keeps the
+    /// nudged position if one of them is clear.
+    fn correct_corner(&mut self, levels: &Levels, position_before_y: [f32; 2]) -> bool {
+        const STEPS: usize = 4;
+
+        let collided_position = self.position;
+
+        for step in 1..=STEPS {
+            for direction in [-1.0, 1.0] {
+                let nudge = direction * Self::CORNER_CORRECTION * step as f32 / STEPS as f32;
+
+                self.position = position_before_y;
+
+                if self.move_by(levels, [nudge, 0.0]) == Some(false)
+                    && self.move_by(levels, [0.0, self.velocity[1]]) == Some(false)
+                {
+                    return true;
+                }
+            }
+        }
+
+        self.position = collided_position;
+
+        false
+    }
+
+    /// Moves the player into the next or previous level once they have gone off the side of the
+    /// screen, making where they come in their spawn point. A double jump picked up in the level
+    /// they left doesn't come with them.
+    fn leave_level(&mut self, levels: &mut Levels) {
+        if self.position[0] > crate::LOGICAL_SCREEN_WIDTH / 2.0 {
+            levels.next_level();
+            self.position[0] = Self::SIZE / 2.0;
+        } else {
+            levels.previous_level();
+            self.position[0] = crate::LOGICAL_SCREEN_WIDTH - Self::SIZE / 2.0;
+        }
+
+        self.previous_position = self.position;
+        self.air_jumps = 0;
+        self.jumps_remaining = 0;
+
+        self.spawn_point = SpawnPoint {
+            position: self.position,
+            air_kind: self.air_kind,
+        };
+    }
+
     pub fn gravity(&self) -> f32 {
         match self.air_kind {
-            true => Self::GRAVITY,
-            false => -Self::GRAVITY,
+            true => self.physics.gravity,
+            false => -self.physics.gravity,
         }
     }
 
-    pub fn is_intersecting(&mut self, levels: &Levels) -> bool {
-        match self.move_by(levels, [0.0, 0.0]) {
-            Some(collision) => collision,
-            None => true,
+    /// Stops the player and starts the death animation, after which they respawn
+    pub fn die(&mut self) {
+        self.deaths += 1;
+        self.death_time = Self::DEATH_FRAMES;
+        self.velocity = [0.0, 0.0];
+    }
+
+    #[must_use]
+    pub fn is_dead(&self) -> bool {
+        self.death_time > 0
+    }
+
+    /// Moves the player into the start of a level, on the lowest spot along its left edge where
+    /// they fit, and makes that their spawn point
+    pub fn enter_level(&mut self, levels: &mut Levels, level_index: usize) {
+        levels.level_index = level_index;
+        levels.update_level_offset();
+
+        self.air_kind = false;
+
+        for y in 0..Levels::LEVEL_HEIGHT {
+            self.position = [Self::SIZE / 2.0, y as f32 + Self::SIZE / 2.0];
+
+            if !self.is_intersecting(levels) {
+                break;
+            }
         }
+
+        self.spawn_point = SpawnPoint {
+            position: self.position,
+            air_kind: self.air_kind,
+        };
+        self.respawn(levels);
+    }
+
+    #[must_use]
+    pub fn is_cursed(&self) -> bool {
+        self.curse_time > 0
     }
 
+    /// Moves the collected coins and checkpoints along with their levels after the levels have
+    /// been rearranged, dropping those in removed levels
+    pub fn remap_levels(&mut self, change: LevelChange) {
+        self.coins = self
+            .coins
+            .iter()
+            .filter_map(|tile_index| change.tile_index(*tile_index))
+            .collect();
+        self.checkpoints = self
+            .checkpoints
+            .iter()
+            .filter_map(|(level_index, spawn_point)| {
+                Some((change.level_index(*level_index)?, *spawn_point))
+            })
+            .collect();
+    }
+
+    /// Sends the player back to the last checkpoint they passed through in the current level, or
+    /// where they entered it if there isn't one, stopping them
+    pub fn respawn(&mut self, levels: &Levels) {
+        let spawn_point = self
+            .checkpoints
+            .get(&levels.level_index)
+            .copied()
+            .unwrap_or(self.spawn_point);
+
+        self.position = spawn_point.position;
+        self.previous_position = self.position;
+        self.air_kind = spawn_point.air_kind;
+        self.death_time = 0;
+        self.curse_time = 0;
+        self.air_jumps = 0;
+        self.jumps_remaining = 0;
+        self.velocity = [0.0, 0.0];
+        self.on_ground = false;
+        self.cyote_time = 0;
+    }
+
+    /// Handles the level changing around the player, which every change to the tiles goes through.
+    /// If the player ended up inside a tile, they are pushed to the nearest spot within a tile of
+    /// where they were that they fit in, or crushed if there isn't one. Returns `true` if the
+    /// player was inside a tile.
+    pub fn resolve_crush(&mut self, levels: &Levels) -> bool {
+        const STEPS: i32 = 16;
+
+        if self.is_dead() || !self.is_intersecting(levels) {
+            return false;
+        }
+
+        let old_position = self.position;
+
+        let mut offsets = (-STEPS..=STEPS)
+            .flat_map(|x| (-STEPS..=STEPS).map(move |y| [x, y]))
+            .filter(|[x, y]| x * x + y * y <= STEPS * STEPS)
+            .collect::<Vec<_>>();
+        offsets.sort_by_key(|[x, y]| x * x + y * y);
+
+        for offset in offsets {
+            self.position = array::from_fn(|i| old_position[i] + offset[i] as f32 / STEPS as f32);
+
+            if !self.is_intersecting(levels) {
+                self.previous_position = self.position;
+                self.velocity = [0.0, 0.0];
+                return true;
+            }
+        }
+
+        self.position = old_position;
+        self.die();
+
+        true
+    }
+
+    /// Returns `true` if the player is close enough to a pickup centered on the given position to
+    /// pick it up
+    #[must_use]
+    pub fn touches_pickup(&self, pickup: Pickup, center: [f32; 2]) -> bool {
+        let distance_squared = (0..2)
+            .map(|i| (center[i] - self.position[i]).powi(2))
+            .sum::<f32>();
+
+        distance_squared < self.physics.pickup_radius(pickup).powi(2)
+    }
+
+    /// The indices of the tiles on the screen of a pickup's kind which the player is touching
+    fn touched_pickup_tiles(&self, levels: &Levels, tile: Tile, pickup: Pickup) -> Vec<usize> {
+        let radius = self.physics.pickup_radius(pickup);
+
+        // The tiles whose centers could be within the radius
+        let range = |axis: usize, size: usize| {
+            let start = (self.position[axis] - radius - 0.5).floor().max(0.0) as usize;
+            let end = (self.position[axis] + radius - 0.5).ceil().max(0.0) as usize;
+
+            start..=end.min(size - 1)
+        };
+        let ys = range(1, Levels::LEVEL_HEIGHT);
+
+        range(0, Levels::LEVEL_WIDTH)
+            .flat_map(|x| ys.clone().map(move |y| [x, y]))
+            .filter(|[x, y]| self.touches_pickup(pickup, [*x as f32 + 0.5, *y as f32 + 0.5]))
+            .filter_map(|position| levels.index_of(position))
+            .filter(|tile_index| levels.tiles[*tile_index] == tile)
+            .collect()
+    }
+
+    /// Returns `true` if the player is blocked by a tile of the given kind which is next to them.
+    /// Only tiles on the other side to the player can block them.
+    pub fn is_touching(&self, levels: &Levels, tile: Tile) -> bool {
+        const CORNERS: [[f32; 2]; 4] = [[1.0, 1.0], [-1.0, 1.0], [-1.0, -1.0], [1.0, -1.0]];
+
+        if tile.is_solid() == self.air_kind {
+            return false;
+        }
+
+        CORNERS.into_iter().any(|corner| {
+            // Reach slightly past the player's edges, since they are pushed out of the tiles
+            // which block them
+            let corner_position =
+                array::from_fn(|i| self.position[i] + corner[i] * (Self::SIZE / 2.0 + 10e-4));
+
+            levels.get_from_position(corner_position) == Some(tile)
+        })
+    }
+
+    pub fn is_intersecting(&mut self, levels: &Levels) -> bool {
+        self.move_by(levels, [0.0, 0.0]).unwrap_or(true)
+    }
+
+    /// Moves the player along one axis until they hit a tile, snapping them against it. Returns
+    /// whether they hit anything, or `None` if they went off the edge of the level.
     pub fn move_by(&mut self, levels: &Levels, amount: [f32; 2]) -> Option<bool> {
+        profile_scope!("collision");
+
+        let distance = amount[0].abs().max(amount[1].abs());
+        let steps = (distance / Self::MAXIMUM_STEP).ceil().max(1.0);
+        let step = amount.map(|amount| amount / steps);
+
+        for _ in 0..steps as usize {
+            if self.move_step(levels, step)? {
+                return Some(true);
+            }
+        }
+
+        Some(false)
+    }
+
+    fn move_step(&mut self, levels: &Levels, amount: [f32; 2]) -> Option<bool> {
         self.position[0] += amount[0];
         self.position[1] += amount[1];
 
@@ -181,7 +579,23 @@ impl Player {
             let corner_position =
                 array::from_fn(|i| self.position[i] + corner[i] * Self::SIZE / 2.0);
 
-            if levels.get_from_position(corner_position)? == self.air_kind {
+            let tile = levels.get_from_position(corner_position)?;
+
+            // One way tiles only block the player while they fall onto them, which is from above
+            // normally and from below while inverted
+            if tile == Tile::OneWay {
+                let previous_y = corner_position[1] - amount[1];
+
+                let falling_onto = if self.air_kind {
+                    amount[1] > 0.0 && previous_y < corner_position[1].floor()
+                } else {
+                    amount[1] < 0.0 && previous_y >= corner_position[1].floor() + 1.0
+                };
+
+                if !falling_onto {
+                    continue;
+                }
+            } else if tile.is_solid() == self.air_kind {
                 continue;
             }
 
@@ -210,3 +624,33 @@ impl Player {
         Some(collision)
     }
 }
+
+impl Hash for Player {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.position.map(f32::to_bits).hash(state);
+        self.velocity.map(f32::to_bits).hash(state);
+        self.air_kind.hash(state);
+        self.on_ground.hash(state);
+        self.cyote_time.hash(state);
+        self.inputs_down.hash(state);
+        self.inputs_ready.hash(state);
+        self.keep_velocity_on_inversion.hash(state);
+        self.spawn_point.hash(state);
+        self.checkpoints.hash(state);
+        self.deaths.hash(state);
+        self.coins.hash(state);
+        self.death_time.hash(state);
+        self.curse_time.hash(state);
+        self.air_jumps.hash(state);
+        self.jumps_remaining.hash(state);
+        self.debug_flags.hash(state);
+        self.fly_speed.to_bits().hash(state);
+    }
+}
+
+impl Hash for SpawnPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.position.map(f32::to_bits).hash(state);
+        self.air_kind.hash(state);
+    }
+}
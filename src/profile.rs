@@ -0,0 +1,118 @@
+use macroquad::{
+    camera,
+    color::{Color, colors},
+    shapes, window,
+};
+use puffin::{GlobalFrameView, GlobalProfiler, NanoSecond, Reader, ScopeCollection, Stream};
+
+use crate::text::{self, TextSize};
+
+/// Keeps the frames recorded by puffin and draws the latest one as a flamegraph
+pub struct Profiler {
+    pub frame_view: GlobalFrameView,
+    pub show_flamegraph: bool,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        puffin::set_scopes_on(true);
+
+        Self {
+            frame_view: GlobalFrameView::default(),
+            show_flamegraph: false,
+        }
+    }
+
+    /// Finishes the previous frame's profile, which should be called at the start of every frame
+    pub fn new_frame(&self) {
+        GlobalProfiler::lock().new_frame();
+    }
+
+    /// Draws the latest frame across the top of the window, with one row per depth of scope
+    pub fn draw_flamegraph(&self) {
+        let frame_view = self.frame_view.lock();
+
+        let Some(frame) = frame_view.latest_frame() else {
+            return;
+        };
+
+        let Some(frame) = frame.unpacked().ok() else {
+            return;
+        };
+
+        let (start_ns, stop_ns) = frame.range_ns();
+        let pixels_per_ns = window::screen_width() / (stop_ns - start_ns).max(1) as f32;
+
+        camera::push_camera_state();
+        camera::set_default_camera();
+
+        for stream_info in frame.thread_streams.values() {
+            let flamegraph = Flamegraph {
+                stream: &stream_info.stream,
+                scopes: frame_view.scope_collection(),
+                start_ns,
+                pixels_per_ns,
+            };
+
+            flamegraph.draw_scopes(Reader::from_start(&stream_info.stream), 0);
+        }
+
+        camera::pop_camera_state();
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Flamegraph<'a> {
+    stream: &'a Stream,
+    scopes: &'a ScopeCollection,
+    start_ns: NanoSecond,
+    pixels_per_ns: f32,
+}
+
+impl Flamegraph<'_> {
+    fn draw_scopes(&self, reader: Reader, depth: usize) {
+        let row_height = text::screen_pixels_per_unit();
+
+        for scope in reader.flatten() {
+            let x = (scope.record.start_ns - self.start_ns) as f32 * self.pixels_per_ns;
+            let y = depth as f32 * row_height;
+            let width = scope.record.duration_ns as f32 * self.pixels_per_ns;
+
+            shapes::draw_rectangle(
+                x,
+                y,
+                width,
+                row_height,
+                Color {
+                    a: 0.75,
+                    ..colors::WHITE
+                },
+            );
+            shapes::draw_rectangle_lines(x, y, width, row_height, 2.0, colors::BLACK);
+
+            if let Some(details) = self.scopes.fetch_by_id(&scope.id) {
+                let label = format!(
+                    "{} {:.2}ms",
+                    details.name(),
+                    scope.record.duration_ns as f32 / 1e6,
+                );
+
+                text::draw_centered_on_screen(
+                    &label,
+                    [x + width / 2.0, y + row_height / 2.0],
+                    TextSize::Small,
+                    colors::BLACK,
+                );
+            }
+
+            if let Ok(children) = Reader::with_offset(self.stream, scope.child_begin_position) {
+                self.draw_scopes(children, depth + 1);
+            }
+        }
+    }
+}
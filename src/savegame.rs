@@ -0,0 +1,317 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    io,
+    str::FromStr,
+};
+
+use crate::debug_flags::DebugFlags;
+use crate::editor::Editor;
+use crate::level::{LevelChange, Levels};
+use crate::player::{Player, SpawnPoint};
+use crate::storage::TrackedFile;
+
+pub const SAVE_FILE_NAME: &str = "save.txt";
+
+/// The player's progress, stored in the data directory as one `key = value` line per field and
+/// per best time or split
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaveGame {
+    /// The level the player was last in, which the game resumes at
+    pub level_index: usize,
+    /// Where the player entered the level they were last in
+    pub spawn_point: Option<SpawnPoint>,
+    pub furthest_level: usize,
+    pub limited_gem_collected: bool,
+    pub full_gem_collected: bool,
+    pub deaths: u32,
+    /// The coins the player has collected, by tile index
+    pub coins: BTreeSet<usize>,
+    /// The fastest time in seconds the player has gone through each level in, by level index
+    pub best_times: BTreeMap<usize, f32>,
+    /// The levels whose best time was set with debug flags on
+    pub debug_times: BTreeSet<usize>,
+    /// The fastest time in seconds since the start of a speedrun that each level has been finished
+    /// at, by level index
+    pub best_splits: BTreeMap<usize, f32>,
+    pub debug_flags: DebugFlags,
+    /// The version of the game the player last saw the changes for
+    pub seen_version: Option<String>,
+}
+
+impl SaveGame {
+    /// Reads the save file, or starts a new save if there isn't one yet
+    pub fn load(file: &mut TrackedFile) -> io::Result<Self> {
+        match file.read() {
+            Ok(text) => text
+                .parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn save(&self, file: &mut TrackedFile) -> io::Result<()> {
+        file.write(self.to_string()).map(|_| ())
+    }
+
+    /// Puts the player back where they were when the game was saved, if that level still exists
+    pub fn resume(&self, levels: &mut Levels, player: &mut Player) {
+        player.deaths = self.deaths;
+        player.coins = self.coins.clone();
+
+        let Some(spawn_point) = self.spawn_point else {
+            return;
+        };
+
+        if self.level_index >= levels.num_levels {
+            return;
+        }
+
+        levels.level_index = self.level_index;
+        levels.update_level_offset();
+
+        player.spawn_point = spawn_point;
+        player.respawn(levels);
+    }
+
+    /// The editor given by the gems the player has collected, if they've collected any, which is
+    /// enabled again when the game starts
+    #[must_use]
+    pub fn unlocked_editor(&self) -> Option<Editor> {
+        if self.full_gem_collected {
+            Some(Editor::Full { drag: None })
+        } else if self.limited_gem_collected {
+            Some(Editor::Limited {
+                last_selected: None,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Updates the save from the current state of the game. Returns `true` if anything changed,
+    /// so the save should be written.
+    pub fn record(&mut self, levels: &Levels, player: &Player) -> bool {
+        let old_save = self.clone();
+
+        self.level_index = levels.level_index;
+        self.spawn_point = Some(player.spawn_point);
+        self.furthest_level = self.furthest_level.max(levels.level_index);
+        self.deaths = player.deaths;
+        self.coins.clone_from(&player.coins);
+
+        *self != old_save
+    }
+
+    /// Moves the player's progress along with the levels it's for after they've been rearranged,
+    /// dropping the progress in removed levels. The levels up to the furthest one stay unlocked,
+    /// wherever they end up.
+    pub fn remap_levels(&mut self, change: LevelChange) {
+        // A removed level is replaced by the one after it, which resuming checks still exists
+        self.level_index = change
+            .level_index(self.level_index)
+            .unwrap_or(self.level_index);
+        self.furthest_level = (0..=self.furthest_level)
+            .filter_map(|level_index| change.level_index(level_index))
+            .max()
+            .unwrap_or_default();
+
+        self.coins = self
+            .coins
+            .iter()
+            .filter_map(|tile_index| change.tile_index(*tile_index))
+            .collect();
+
+        for times in [&mut self.best_times, &mut self.best_splits] {
+            *times = times
+                .iter()
+                .filter_map(|(level_index, time)| Some((change.level_index(*level_index)?, *time)))
+                .collect();
+        }
+
+        self.debug_times = self
+            .debug_times
+            .iter()
+            .filter_map(|level_index| change.level_index(*level_index))
+            .collect();
+    }
+
+    /// Records the time the player took to go through a level. Returns `true` if it's their
+    /// best yet. If debug flags were on at any point while going through the level, `debug` is
+    /// `true` and the time is marked as a debug time, which never replaces a real one, while a
+    /// real one always replaces a debug one.
+    pub fn record_time(&mut self, level_index: usize, time: f32, debug: bool) -> bool {
+        let best = match self.best_times.get(&level_index) {
+            None => true,
+            Some(best_time) => match (debug, self.debug_times.contains(&level_index)) {
+                (false, true) => true,
+                (true, false) => false,
+                _ => time < *best_time,
+            },
+        };
+
+        if best {
+            self.best_times.insert(level_index, time);
+
+            if debug {
+                self.debug_times.insert(level_index);
+            } else {
+                self.debug_times.remove(&level_index);
+            }
+        }
+
+        best
+    }
+
+    /// Records the time since the start of a speedrun that a level was finished at. Returns `true`
+    /// if it's the best split yet. If debug flags were on at any point in the run, `debug` is
+    /// `true` and the split isn't recorded at all.
+    pub fn record_split(&mut self, level_index: usize, time: f32, debug: bool) -> bool {
+        if debug {
+            return false;
+        }
+
+        match self.best_splits.get(&level_index) {
+            Some(best_split) if *best_split <= time => false,
+            _ => {
+                self.best_splits.insert(level_index, time);
+                true
+            }
+        }
+    }
+}
+
+impl Display for SaveGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "level = {}", self.level_index)?;
+
+        if let Some(SpawnPoint { position, air_kind }) = self.spawn_point {
+            writeln!(f, "spawn = {} {} {air_kind}", position[0], position[1])?;
+        }
+
+        writeln!(f, "furthest_level = {}", self.furthest_level)?;
+        writeln!(f, "limited_gem = {}", self.limited_gem_collected)?;
+        writeln!(f, "full_gem = {}", self.full_gem_collected)?;
+        writeln!(f, "deaths = {}", self.deaths)?;
+
+        if !self.coins.is_empty() {
+            write!(f, "coins =")?;
+
+            for tile_index in &self.coins {
+                write!(f, " {tile_index}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        for (level_index, time) in &self.best_times {
+            if self.debug_times.contains(level_index) {
+                writeln!(f, "best_time = {level_index} {time} debug")?;
+            } else {
+                writeln!(f, "best_time = {level_index} {time}")?;
+            }
+        }
+
+        for (level_index, time) in &self.best_splits {
+            writeln!(f, "best_split = {level_index} {time}")?;
+        }
+
+        if self.debug_flags.any() {
+            writeln!(
+                f,
+                "debug_flags = {}",
+                self.debug_flags.enabled().collect::<Vec<_>>().join(" ")
+            )?;
+        }
+
+        if let Some(version) = &self.seen_version {
+            writeln!(f, "seen_version = {version}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SaveGame {
+    type Err = ParseSaveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut save = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let invalid = ParseSaveError(i);
+
+            let (key, value) = line.split_once('=').ok_or(invalid)?;
+            let mut values = value.split_whitespace();
+            let mut next = || values.next().ok_or(invalid);
+
+            match key.trim() {
+                "level" => save.level_index = next()?.parse().map_err(|_| invalid)?,
+                "spawn" => {
+                    save.spawn_point = Some(SpawnPoint {
+                        position: [
+                            next()?.parse().map_err(|_| invalid)?,
+                            next()?.parse().map_err(|_| invalid)?,
+                        ],
+                        air_kind: next()?.parse().map_err(|_| invalid)?,
+                    });
+                }
+                "furthest_level" => save.furthest_level = next()?.parse().map_err(|_| invalid)?,
+                "limited_gem" => {
+                    save.limited_gem_collected = next()?.parse().map_err(|_| invalid)?;
+                }
+                "full_gem" => save.full_gem_collected = next()?.parse().map_err(|_| invalid)?,
+                "deaths" => save.deaths = next()?.parse().map_err(|_| invalid)?,
+                "coins" => {
+                    for tile_index in values.by_ref() {
+                        save.coins.insert(tile_index.parse().map_err(|_| invalid)?);
+                    }
+                }
+                "best_time" => {
+                    let level_index = next()?.parse().map_err(|_| invalid)?;
+                    let time = next()?.parse().map_err(|_| invalid)?;
+
+                    save.best_times.insert(level_index, time);
+
+                    match values.next() {
+                        Some("debug") => _ = save.debug_times.insert(level_index),
+                        Some(_) => return Err(invalid),
+                        None => {}
+                    }
+                }
+                "best_split" => {
+                    let level_index = next()?.parse().map_err(|_| invalid)?;
+                    let time = next()?.parse().map_err(|_| invalid)?;
+
+                    save.best_splits.insert(level_index, time);
+                }
+                "debug_flags" => {
+                    for name in values.by_ref() {
+                        *save.debug_flags.get_mut(name).ok_or(invalid)? = true;
+                    }
+                }
+                "seen_version" => save.seen_version = Some(next()?.to_owned()),
+                _ => return Err(invalid),
+            }
+        }
+
+        Ok(save)
+    }
+}
+
+/// A line of the save file which couldn't be read, by its index
+#[derive(Clone, Copy, Debug)]
+pub struct ParseSaveError(pub usize);
+
+impl Display for ParseSaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid save data on line {}", self.0 + 1)
+    }
+}
+
+impl std::error::Error for ParseSaveError {}
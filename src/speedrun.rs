@@ -0,0 +1,109 @@
+use crate::level::Levels;
+use crate::player::Player;
+use crate::savegame::SaveGame;
+
+/// Times a run through every level in order from the first, starting on the player's first input
+/// in the first level. Time is counted in updates, so it isn't affected by pausing or frame rate.
+#[derive(Clone, Debug, Default)]
+pub struct SpeedrunTimer {
+    /// The number of updates since the run started, or `None` if it hasn't yet
+    updates: Option<u32>,
+    /// The time in seconds since the start of the run that each level was finished at, by level
+    /// index
+    pub splits: Vec<f32>,
+    /// The difference in seconds between the last split and the best one before it, if there was
+    /// a best split to compare with
+    last_delta: Option<f32>,
+    /// Whether debug flags have been on at any point since the run started, which keeps its splits
+    /// out of the save
+    used_debug_flags: bool,
+}
+
+impl SpeedrunTimer {
+    /// Counts an update of the run, starting it if the player is pressing anything in the first
+    /// level. `debug` is whether debug flags are on during the update. This should be called
+    /// before [`Player::update`], which clears the inputs.
+    pub fn update(&mut self, levels: &Levels, player: &Player, debug: bool) {
+        if self.is_finished(levels) {
+            return;
+        }
+
+        match &mut self.updates {
+            Some(updates) => {
+                *updates += 1;
+                self.used_debug_flags |= debug;
+            }
+            None if levels.level_index == 0 && player.inputs_down.contains(&true) => {
+                self.updates = Some(0);
+                self.used_debug_flags = debug;
+            }
+            None => {}
+        }
+    }
+
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.updates.is_some()
+    }
+
+    /// Returns `true` once every level has been finished
+    #[must_use]
+    pub fn is_finished(&self, levels: &Levels) -> bool {
+        self.splits.len() >= levels.num_levels
+    }
+
+    /// The time in seconds since the start of the run
+    #[must_use]
+    pub fn time(&self) -> f32 {
+        self.updates.unwrap_or(0) as f32 / Player::UPDATES_PER_SECOND
+    }
+
+    /// Records a split for going forwards out of a level, if it's the next level of the run, and
+    /// keeps it in the save if it's the best yet. Returns `true` if the save changed.
+    pub fn split(&mut self, level_index: usize, savegame: &mut SaveGame) -> bool {
+        if !self.is_running() || level_index != self.splits.len() {
+            return false;
+        }
+
+        let time = self.time();
+
+        self.last_delta = savegame
+            .best_splits
+            .get(&level_index)
+            .map(|best_split| time - best_split);
+        self.splits.push(time);
+
+        savegame.record_split(level_index, time, self.used_debug_flags)
+    }
+
+    /// How far ahead (negative) or behind (positive) of the best split the run is in seconds. This
+    /// is live while the run is going, and the final split's once it is finished.
+    #[must_use]
+    pub fn delta(&self, levels: &Levels, savegame: &SaveGame) -> Option<f32> {
+        if self.is_finished(levels) {
+            return self.last_delta;
+        }
+
+        if !self.is_running() {
+            return None;
+        }
+
+        savegame
+            .best_splits
+            .get(&self.splits.len())
+            .map(|best_split| self.time() - best_split)
+    }
+}
+
+/// Writes a time in seconds as minutes, seconds and hundredths, such as `1:05.25`
+#[must_use]
+pub fn format_time(time: f32) -> String {
+    let hundredths = (time.abs() * 100.0).round() as u32;
+
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
@@ -0,0 +1,91 @@
+use std::{fmt::Display, fs, io, path::PathBuf};
+
+use inverse::storage;
+
+const STATUS_FILE_NAME: &str = "status.json";
+
+/// Seconds between writes of the status file
+const WRITE_INTERVAL: f64 = 1.0;
+
+/// Periodically writes what the player is doing to a small JSON file in the data directory, for
+/// stream overlays and other external tools to read.
+#[derive(Clone, Debug)]
+pub struct StatusFile {
+    pub path: PathBuf,
+    pub last_write: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Activity {
+    Playing,
+    Editing,
+    Paused,
+}
+
+impl StatusFile {
+    pub fn new() -> Self {
+        Self {
+            path: storage::data_directory().join(STATUS_FILE_NAME),
+            last_write: None,
+        }
+    }
+
+    /// Writes the status file if enough time has passed since it was last written
+    pub fn update(
+        &mut self,
+        activity: Activity,
+        level_index: usize,
+        num_levels: usize,
+        deaths: u32,
+    ) {
+        let time = macroquad::time::get_time();
+
+        if let Some(last_write) = self.last_write
+            && time - last_write < WRITE_INTERVAL
+        {
+            return;
+        }
+
+        self.last_write = Some(time);
+
+        // Overlays only need a best effort, so don't interrupt the game if this fails
+        let _ = self.write(activity, level_index, num_levels, deaths, time);
+    }
+
+    fn write(
+        &self,
+        activity: Activity,
+        level_index: usize,
+        num_levels: usize,
+        deaths: u32,
+        elapsed_time: f64,
+    ) -> io::Result<()> {
+        fs::write(
+            &self.path,
+            format!(
+                "{{\"activity\":\"{activity}\",\"level\":{},\"num_levels\":{num_levels},\"deaths\":{deaths},\"elapsed_seconds\":{elapsed_time:.1}}}\n",
+                level_index + 1,
+            ),
+        )
+    }
+}
+
+impl Default for StatusFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for Activity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Activity::Playing => "playing",
+                Activity::Editing => "editing",
+                Activity::Paused => "paused",
+            }
+        )
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Environment variable that overrides the directory files are read from and written to
+pub const DATA_DIRECTORY_VARIABLE: &str = "INVERSE_DATA_DIR";
+
+const VERSION_FILE_NAME: &str = "version.txt";
+const BACKUP_DIRECTORY_NAME: &str = "backups";
+
+pub fn data_directory() -> PathBuf {
+    match env::var_os(DATA_DIRECTORY_VARIABLE) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// If the game's version has changed since it last ran, copies the given files from the data
+/// directory into a backup folder named after the previous version, so nothing is lost if the
+/// new version migrates or breaks them.
+///
+/// Returns the backup folder if one was made
+pub fn back_up_on_version_change(file_names: &[&str]) -> io::Result<Option<PathBuf>> {
+    let data_directory = data_directory();
+    let version_path = data_directory.join(VERSION_FILE_NAME);
+    let version = env!("CARGO_PKG_VERSION");
+
+    let previous_version = match fs::read_to_string(&version_path) {
+        Ok(previous_version) => Some(previous_version.trim().to_owned()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error),
+    };
+
+    if previous_version.as_deref() == Some(version) {
+        return Ok(None);
+    }
+
+    let existing_files = file_names
+        .iter()
+        .filter(|file_name| data_directory.join(file_name).exists())
+        .collect::<Vec<_>>();
+
+    let backup = if existing_files.is_empty() {
+        None
+    } else {
+        let backup = data_directory
+            .join(BACKUP_DIRECTORY_NAME)
+            .join(previous_version.as_deref().unwrap_or("unknown"));
+
+        fs::create_dir_all(&backup)?;
+
+        for file_name in existing_files {
+            fs::copy(data_directory.join(file_name), backup.join(file_name))?;
+        }
+
+        Some(backup)
+    };
+
+    fs::write(version_path, version)?;
+
+    Ok(backup)
+}
+
+/// Returns the most recently made backup folder, if there are any
+pub fn latest_backup() -> Option<PathBuf> {
+    fs::read_dir(data_directory().join(BACKUP_DIRECTORY_NAME))
+        .ok()?
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
+/// A file in the data directory which remembers when it was last read or written, so that writes
+/// made by another machine sharing the directory (e.g. through a synced folder) aren't silently
+/// overwritten. Writes replace the file all at once, and keep the last few versions as `.bak1`,
+/// `.bak2` and so on, newest first.
+#[derive(Clone, Debug)]
+pub struct TrackedFile {
+    pub path: PathBuf,
+    pub last_modified: Option<SystemTime>,
+}
+
+impl TrackedFile {
+    /// The number of old versions kept when the file is written
+    pub const BACKUPS: usize = 3;
+
+    pub fn new(file_name: impl AsRef<Path>) -> Self {
+        Self {
+            path: data_directory().join(file_name),
+            last_modified: None,
+        }
+    }
+
+    pub fn read(&mut self) -> io::Result<String> {
+        let contents = fs::read_to_string(&self.path)?;
+        self.last_modified = modified_time(&self.path);
+
+        Ok(contents)
+    }
+
+    /// Writes the file, with the last writer winning. If the file was changed by someone else
+    /// since it was last read or written, the other version is moved to a `.conflict` file first.
+    ///
+    /// Returns whether or not there was a conflict
+    pub fn write(&mut self, contents: impl AsRef<[u8]>) -> io::Result<bool> {
+        let conflict = self.has_conflict();
+
+        if conflict {
+            fs::rename(&self.path, self.conflict_path())?;
+        } else if self.path.exists() {
+            self.rotate_backups()?;
+        }
+
+        // Writing to a temporary file and renaming it over the real one means the file is never
+        // left half written if the game stops partway through
+        let temporary_path = self.path_with_extension("tmp");
+
+        {
+            let mut file = fs::File::create(&temporary_path)?;
+
+            file.write_all(contents.as_ref())?;
+            // Otherwise the rename can reach the disk before the contents do, leaving an empty
+            // file in place of the real one after a power cut
+            file.sync_all()?;
+        }
+
+        fs::rename(&temporary_path, &self.path)?;
+        sync_parent_directory(&self.path);
+        self.last_modified = modified_time(&self.path);
+
+        Ok(conflict)
+    }
+
+    /// Replaces the file with one of its backups, numbered from 1 for the newest. The version
+    /// being replaced becomes the newest backup, so restoring can be undone.
+    pub fn restore_backup(&mut self, number: usize) -> io::Result<()> {
+        let contents = fs::read(self.backup_path(number))?;
+
+        self.write(contents).map(|_| ())
+    }
+
+    pub fn backup_path(&self, number: usize) -> PathBuf {
+        self.path_with_extension(&format!("bak{number}"))
+    }
+
+    /// Shifts each backup along by one, dropping the oldest, and copies the file into the first.
+    /// The file is copied rather than moved so that it's still there if writing the new version
+    /// fails.
+    fn rotate_backups(&self) -> io::Result<()> {
+        for number in (1..Self::BACKUPS).rev() {
+            let backup = self.backup_path(number);
+
+            if backup.exists() {
+                fs::rename(backup, self.backup_path(number + 1))?;
+            }
+        }
+
+        fs::copy(&self.path, self.backup_path(1)).map(|_| ())
+    }
+
+    pub fn has_conflict(&self) -> bool {
+        match (self.last_modified, modified_time(&self.path)) {
+            (Some(last_modified), Some(modified)) => modified != last_modified,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        }
+    }
+
+    /// Moves the file aside to a `.corrupt` file, for a file which couldn't be loaded, so that
+    /// writing it again doesn't replace the only copy. Returns the path it was moved to.
+    pub fn set_aside_corrupt(&mut self) -> io::Result<PathBuf> {
+        // If it can't be moved, the next write sees a conflict and keeps it that way instead
+        self.last_modified = None;
+
+        let corrupt_path = self.path_with_extension("corrupt");
+        fs::rename(&self.path, &corrupt_path)?;
+
+        Ok(corrupt_path)
+    }
+
+    pub fn conflict_path(&self) -> PathBuf {
+        self.path_with_extension("conflict")
+    }
+
+    /// The file's path with another extension added after its own
+    fn path_with_extension(&self, extension: &str) -> PathBuf {
+        let mut path = OsString::from(&self.path);
+        path.push(".");
+        path.push(extension);
+
+        path.into()
+    }
+}
+
+/// Tracks whether there are changes which haven't been written yet, so that a burst of edits is
+/// written once rather than after every one of them
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Autosave {
+    /// The time in seconds since the oldest unsaved change, or `None` if there aren't any
+    unsaved_time: Option<f32>,
+}
+
+impl Autosave {
+    /// How long changes can go unsaved before they're written
+    pub const INTERVAL: f32 = 10.0;
+
+    pub fn mark_dirty(&mut self) {
+        self.unsaved_time.get_or_insert(0.0);
+    }
+
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.unsaved_time.is_some()
+    }
+
+    /// Advances the time since the oldest unsaved change, returning whether it's time to save
+    pub fn update(&mut self, seconds: f32) -> bool {
+        match &mut self.unsaved_time {
+            Some(time) => {
+                *time += seconds;
+                *time >= Self::INTERVAL
+            }
+            None => false,
+        }
+    }
+
+    /// Writes the contents to the file, counting the changes as saved if it works. If it doesn't,
+    /// the next autosave is a whole interval away so a failing write isn't retried every frame.
+    pub fn save(&mut self, file: &mut TrackedFile, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        match file.write(contents) {
+            Ok(_) => {
+                self.unsaved_time = None;
+                Ok(())
+            }
+            Err(error) => {
+                self.unsaved_time = Some(0.0);
+                Err(error)
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Makes sure a rename into the directory holding `path` reaches the disk. This is only possible
+/// on unix, and failing to isn't worth failing the write over since the file itself is written.
+fn sync_parent_directory(path: &Path) {
+    #[cfg(unix)]
+    {
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        if let Ok(directory) = fs::File::open(directory) {
+            let _ = directory.sync_all();
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+}
@@ -0,0 +1,115 @@
+use std::sync::LazyLock;
+
+use macroquad::{
+    camera,
+    color::Color,
+    text::{self, Font, TextDimensions, TextParams},
+    window,
+};
+
+static FONT: LazyLock<Font> = LazyLock::new(|| {
+    text::load_ttf_font_from_bytes(include_bytes!("../assets/fonts/DejaVuSansMono-Bold.ttf"))
+        .unwrap()
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextSize {
+    Small,
+    Medium,
+    Large,
+    Title,
+}
+
+impl TextSize {
+    /// The height of the text in world units. In screen space this is scaled by the same amount
+    /// the world would be.
+    #[must_use]
+    pub const fn height(self) -> f32 {
+        match self {
+            TextSize::Small => 0.375,
+            TextSize::Medium => 0.5,
+            TextSize::Large => 0.75,
+            TextSize::Title => 1.25,
+        }
+    }
+}
+
+/// Returns the width and height of the text in world units
+#[must_use]
+pub fn measure(text: &str, size: TextSize) -> [f32; 2] {
+    let (font_size, font_scale, _) = text::camera_font_scale(size.height());
+
+    let TextDimensions { width, height, .. } =
+        text::measure_text(text, Some(&FONT), font_size, font_scale);
+
+    [width, height]
+}
+
+/// Draws text in world space, centered on a position
+pub fn draw_centered(text: &str, center: [f32; 2], size: TextSize, color: Color) {
+    let [width, _] = measure(text, size);
+
+    draw_left_aligned(text, [center[0] - width / 2.0, center[1]], size, color);
+}
+
+/// Draws text in world space, with the center of its left edge at a position
+pub fn draw_left_aligned(text: &str, position: [f32; 2], size: TextSize, color: Color) {
+    let (font_size, font_scale, font_scale_aspect) = text::camera_font_scale(size.height());
+
+    let TextDimensions { height, .. } =
+        text::measure_text(text, Some(&FONT), font_size, font_scale);
+
+    // The world camera is flipped vertically relative to the screen, so the text has to be too
+    text::draw_text_ex(
+        text,
+        position[0],
+        position[1] - height / 2.0,
+        TextParams {
+            font: Some(&FONT),
+            font_size,
+            font_scale: -font_scale,
+            font_scale_aspect: -font_scale_aspect,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+/// Draws text in screen space, centered on a position in pixels from the top left of the window
+pub fn draw_centered_on_screen(text: &str, center: [f32; 2], size: TextSize, color: Color) {
+    let font_size = (size.height() * screen_pixels_per_unit()) as u16;
+
+    let TextDimensions { width, .. } = text::measure_text(text, Some(&FONT), font_size, 1.0);
+
+    draw_left_aligned_on_screen(text, [center[0] - width / 2.0, center[1]], size, color);
+}
+
+/// Draws text in screen space, with the center of its left edge at a position in pixels from the
+/// top left of the window
+pub fn draw_left_aligned_on_screen(text: &str, position: [f32; 2], size: TextSize, color: Color) {
+    let font_size = (size.height() * screen_pixels_per_unit()) as u16;
+
+    let TextDimensions { height, .. } = text::measure_text(text, Some(&FONT), font_size, 1.0);
+
+    camera::push_camera_state();
+    camera::set_default_camera();
+
+    text::draw_text_ex(
+        text,
+        position[0],
+        position[1] + height / 2.0,
+        TextParams {
+            font: Some(&FONT),
+            font_size,
+            color,
+            ..Default::default()
+        },
+    );
+
+    camera::pop_camera_state();
+}
+
+/// The number of pixels one world unit takes up on the screen
+pub fn screen_pixels_per_unit() -> f32 {
+    window::screen_height() / crate::get_window_height()
+}
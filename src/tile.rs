@@ -0,0 +1,96 @@
+/// The kind of a single tile in a level. Every tile belongs to either the solid or the empty side,
+/// which decides which of the two kinds of player it blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    #[default]
+    Empty,
+    Solid,
+    /// A solid tile which sends the player back to their spawn point when touched from the air
+    Spike,
+    /// A solid tile which can only be landed on by falling onto it, from above or from below while
+    /// inverted, and can be passed through otherwise
+    OneWay,
+    /// An empty tile which the player respawns at after passing through it
+    Checkpoint,
+    /// An empty tile which mirrors the player's horizontal controls for a while after they pass
+    /// through it
+    Curse,
+    /// An empty tile which flips the plain tiles in its level's switch region between solid and
+    /// empty when the player passes into it
+    Switch,
+    /// An empty tile with a coin in it, which the player collects by passing through it
+    Coin,
+    /// An empty tile which lets the player jump once in the air, refreshed each time they land,
+    /// until they respawn or leave the level
+    DoubleJump,
+}
+
+impl Tile {
+    pub const ALL: [Tile; 9] = [
+        Tile::Empty,
+        Tile::Solid,
+        Tile::Spike,
+        Tile::OneWay,
+        Tile::Checkpoint,
+        Tile::Curse,
+        Tile::Switch,
+        Tile::Coin,
+        Tile::DoubleJump,
+    ];
+
+    #[must_use]
+    pub fn is_solid(self) -> bool {
+        match self {
+            Tile::Solid | Tile::Spike | Tile::OneWay => true,
+            Tile::Empty
+            | Tile::Checkpoint
+            | Tile::Curse
+            | Tile::Switch
+            | Tile::Coin
+            | Tile::DoubleJump => false,
+        }
+    }
+
+    /// Returns `true` if the tile is [`Empty`] or [`Solid`], with no behavior of its own.
+    ///
+    /// [`Empty`]: Tile::Empty
+    /// [`Solid`]: Tile::Solid
+    #[must_use]
+    pub fn is_plain(self) -> bool {
+        matches!(self, Tile::Empty | Tile::Solid)
+    }
+
+    /// The plain tile on the other side to this one, which is what the editor turns it into
+    #[must_use]
+    pub fn toggled(self) -> Self {
+        if self.is_solid() {
+            Tile::Empty
+        } else {
+            Tile::Solid
+        }
+    }
+
+    /// The character used for the tile in the levels file
+    #[must_use]
+    pub fn character(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Solid => 'x',
+            Tile::Spike => '^',
+            Tile::OneWay => '-',
+            Tile::Checkpoint => 'c',
+            Tile::Curse => '?',
+            Tile::Switch => 's',
+            Tile::Coin => 'o',
+            Tile::DoubleJump => 'j',
+        }
+    }
+
+    #[must_use]
+    pub fn from_character(character: char) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tile| tile.character() == character)
+    }
+}
@@ -0,0 +1,34 @@
+use crate::player::Player;
+
+/// Turns the time between frames into a whole number of simulation ticks, so the simulation only
+/// ever steps by a fixed amount and plays out the same at any frame rate
+#[derive(Clone, Debug, Default)]
+pub struct FixedTimestep {
+    /// The number of ticks run so far
+    pub tick: u64,
+    /// Time from previous frames which didn't make up a whole tick, in ticks
+    remainder: f64,
+}
+
+impl FixedTimestep {
+    /// Adds the time a frame took, scaled by the game speed, and returns how many ticks to run for
+    /// it. At most [`Player::MAXIMUM_UPDATES_PER_FRAME`] are run, so a long frame slows the game
+    /// down instead of skipping through it.
+    pub fn advance(&mut self, frame_time: f32, game_speed: f32) -> usize {
+        self.remainder +=
+            f64::from(frame_time) * f64::from(Player::UPDATES_PER_SECOND) * f64::from(game_speed);
+
+        let ticks = (self.remainder as usize).min(Player::MAXIMUM_UPDATES_PER_FRAME);
+
+        self.remainder = (self.remainder - ticks as f64).min(1.0);
+        self.tick += ticks as u64;
+
+        ticks
+    }
+
+    /// How far through the next tick the game is, from 0 to 1, for drawing between ticks
+    #[must_use]
+    pub fn fraction(&self) -> f32 {
+        self.remainder.clamp(0.0, 1.0) as f32
+    }
+}
@@ -0,0 +1,253 @@
+use macroquad::{
+    camera::Camera2D,
+    color::colors,
+    input::{self, KeyCode, MouseButton},
+    shapes,
+};
+
+use inverse::changelog::Release;
+use inverse::keybinds::Keybinds;
+use inverse::level::Levels;
+use inverse::savegame::SaveGame;
+
+use crate::text::{self, TextSize};
+use crate::ui::{self, Menu, MenuState};
+
+const GRID_COLUMNS: usize = 7;
+const CELL_SIZE: f32 = 1.0;
+const CELL_SPACING: f32 = 1.25;
+
+/// The most changes shown on the what's new screen, so they fit on it
+const MAX_CHANGE_LINES: usize = 12;
+
+/// What the game is showing. The title, level select and what's new screens are drawn over the
+/// level.
+#[derive(Clone, Debug)]
+pub enum Screen {
+    Title {
+        menu: MenuState,
+    },
+    LevelSelect {
+        focus: usize,
+    },
+    /// The changes since the version the player last played, shown before the title screen
+    WhatsNew {
+        releases: Vec<Release>,
+    },
+    Playing,
+}
+
+/// What the player chose on the title or level select screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenAction {
+    /// Continue from the level the player was last in
+    Continue,
+    PlayLevel(usize),
+    Quit,
+}
+
+impl Screen {
+    pub fn title() -> Self {
+        Screen::Title {
+            menu: MenuState::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_playing(&self) -> bool {
+        matches!(self, Screen::Playing)
+    }
+
+    /// Draws the screen and handles its input, where `levels` are the levels to select from and
+    /// `savegame` says which of them are complete
+    pub fn update(
+        &mut self,
+        levels: &Levels,
+        savegame: &SaveGame,
+        keybinds: &Keybinds,
+        camera: &Camera2D,
+    ) -> Option<ScreenAction> {
+        let num_levels = levels.num_levels;
+
+        match self {
+            Screen::Title { menu } => {
+                text::draw_centered("INVERSE", [0.0, 3.0], TextSize::Title, colors::WHITE);
+
+                let mut menu = Menu::begin(menu, keybinds);
+
+                let continued = menu.button("CONTINUE");
+                let level_select = menu.button("LEVEL SELECT");
+                let quit = menu.button("QUIT");
+
+                menu.end();
+
+                if level_select {
+                    *self = Screen::LevelSelect {
+                        focus: savegame.level_index.min(num_levels - 1),
+                    };
+                }
+
+                if continued {
+                    Some(ScreenAction::Continue)
+                } else if quit {
+                    Some(ScreenAction::Quit)
+                } else {
+                    None
+                }
+            }
+            Screen::LevelSelect { focus } => {
+                let selected = update_level_select(focus, levels, savegame, keybinds, camera);
+
+                if input::is_key_pressed(KeyCode::Escape) {
+                    *self = Self::title();
+                }
+
+                selected.map(ScreenAction::PlayLevel)
+            }
+            Screen::WhatsNew { releases } => {
+                draw_whats_new(releases);
+
+                if [KeyCode::Enter, KeyCode::Space, KeyCode::Escape]
+                    .into_iter()
+                    .any(input::is_key_pressed)
+                    || input::is_mouse_button_pressed(MouseButton::Left)
+                {
+                    *self = Self::title();
+                }
+
+                None
+            }
+            Screen::Playing => None,
+        }
+    }
+}
+
+/// Draws the changes in each release under its version, leaving out the oldest changes if there
+/// are too many to fit
+fn draw_whats_new(releases: &[Release]) {
+    text::draw_centered("WHAT'S NEW", [0.0, 4.0], TextSize::Large, colors::WHITE);
+
+    let lines = releases
+        .iter()
+        .flat_map(|release| {
+            let version = (format!("VERSION {}", release.version), colors::WHITE);
+            let changes = release
+                .changes
+                .iter()
+                .map(|change| (change.to_uppercase(), colors::GRAY));
+
+            [version].into_iter().chain(changes)
+        })
+        .take(MAX_CHANGE_LINES);
+
+    for (i, (line, color)) in lines.enumerate() {
+        text::draw_centered(&line, [0.0, 3.0 - i as f32 * 0.5], TextSize::Small, color);
+    }
+
+    text::draw_centered(
+        "ENTER TO CONTINUE",
+        [0.0, -4.0],
+        TextSize::Small,
+        colors::WHITE,
+    );
+}
+
+/// Draws a grid of the levels, with complete levels filled in and a badge on levels with all
+/// their coins collected, and returns the level the player selects
+fn update_level_select(
+    focus: &mut usize,
+    levels: &Levels,
+    savegame: &SaveGame,
+    keybinds: &Keybinds,
+    camera: &Camera2D,
+) -> Option<usize> {
+    let num_levels = levels.num_levels;
+
+    text::draw_centered("LEVEL SELECT", [0.0, 3.5], TextSize::Large, colors::WHITE);
+
+    let mut activated = ui::navigate_grid(focus, num_levels, GRID_COLUMNS, keybinds);
+
+    let mouse_position = <[f32; 2]>::from(camera.screen_to_world(input::mouse_position().into()));
+    let clicked = input::is_mouse_button_pressed(MouseButton::Left);
+
+    let num_rows = num_levels.div_ceil(GRID_COLUMNS);
+    let columns_in_use = num_levels.min(GRID_COLUMNS);
+
+    for level_index in 0..num_levels {
+        let column = level_index % GRID_COLUMNS;
+        let row = level_index / GRID_COLUMNS;
+
+        let center = [
+            (column as f32 - (columns_in_use - 1) as f32 / 2.0) * CELL_SPACING,
+            ((num_rows - 1) as f32 / 2.0 - row as f32) * CELL_SPACING,
+        ];
+        let corner = center.map(|x| x - CELL_SIZE / 2.0);
+
+        let hovered = (0..2).all(|i| (mouse_position[i] - center[i]).abs() < CELL_SIZE / 2.0);
+
+        if hovered && clicked {
+            *focus = level_index;
+            activated = true;
+        }
+
+        let complete = savegame.best_times.contains_key(&level_index);
+        let focused = *focus == level_index;
+
+        // Levels only completed with debug flags on are grayed out
+        if complete {
+            let color = if savegame.debug_times.contains(&level_index) {
+                colors::GRAY
+            } else {
+                colors::WHITE
+            };
+
+            shapes::draw_rectangle(corner[0], corner[1], CELL_SIZE, CELL_SIZE, color);
+        }
+
+        shapes::draw_rectangle_lines(
+            corner[0],
+            corner[1],
+            CELL_SIZE,
+            CELL_SIZE,
+            0.0625,
+            colors::WHITE,
+        );
+
+        if focused {
+            shapes::draw_rectangle_lines(
+                corner[0] - 0.125,
+                corner[1] - 0.125,
+                CELL_SIZE + 0.25,
+                CELL_SIZE + 0.25,
+                0.0625,
+                colors::WHITE,
+            );
+        }
+
+        let foreground = if complete {
+            colors::BLACK
+        } else {
+            colors::WHITE
+        };
+
+        text::draw_centered(
+            &(level_index + 1).to_string(),
+            center,
+            TextSize::Medium,
+            foreground,
+        );
+
+        let mut coins = levels.coins(level_index).peekable();
+
+        if coins.peek().is_some() && coins.all(|tile_index| savegame.coins.contains(&tile_index)) {
+            shapes::draw_circle(
+                corner[0] + CELL_SIZE - 0.1875,
+                corner[1] + CELL_SIZE - 0.1875,
+                0.09375,
+                foreground,
+            );
+        }
+    }
+
+    activated.then_some(*focus)
+}
@@ -0,0 +1,268 @@
+use std::ops::RangeInclusive;
+
+use macroquad::{
+    color::{Color, colors},
+    input::{self, KeyCode},
+    shapes,
+};
+
+use inverse::keybinds::{Action, Keybinds};
+
+use crate::text::{self, TextSize};
+
+const ROW_HEIGHT: f32 = 0.875;
+const ROW_WIDTH: f32 = 10.0;
+const ROW_PADDING: f32 = 0.25;
+const TEXT_SIZE: TextSize = TextSize::Medium;
+
+const SLIDER_WIDTH: f32 = 3.0;
+
+const ACTIVATE_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::Space];
+
+/// The part of a menu which persists between frames
+#[derive(Clone, Debug, Default)]
+pub struct MenuState {
+    pub focus: usize,
+    /// The number of items in the menu last frame, used for wrapping and centering
+    pub num_items: usize,
+}
+
+/// An immediate mode menu, laid out as a vertical list of items centered on the camera. Items are
+/// drawn and handle their input as soon as they are added, and the menu is finished with
+/// [`Menu::end`]. The focus is moved with the keys bound to the player's movement.
+pub struct Menu<'a> {
+    state: &'a mut MenuState,
+    index: usize,
+    top: f32,
+    activated: bool,
+    direction: isize,
+}
+
+impl<'a> Menu<'a> {
+    pub fn begin(state: &'a mut MenuState, keybinds: &Keybinds) -> Self {
+        if state.num_items > 0 {
+            if is_direction_pressed(keybinds, Action::Jump) {
+                state.focus = (state.focus + state.num_items - 1) % state.num_items;
+            }
+
+            if is_direction_pressed(keybinds, Action::Invert) {
+                state.focus = (state.focus + 1) % state.num_items;
+            }
+        }
+
+        let direction = is_direction_pressed(keybinds, Action::Right) as isize
+            - is_direction_pressed(keybinds, Action::Left) as isize;
+
+        Self {
+            top: state.num_items as f32 * ROW_HEIGHT / 2.0,
+            state,
+            index: 0,
+            activated: is_any_key_pressed(&ACTIVATE_KEYS),
+            direction,
+        }
+    }
+
+    /// Returns `true` if the button was pressed this frame
+    pub fn button(&mut self, label: &str) -> bool {
+        let focused = self.next_item(label);
+
+        focused && self.activated
+    }
+
+    /// Returns `true` if the value was changed this frame
+    pub fn toggle(&mut self, label: &str, value: &mut bool) -> bool {
+        let focused = self.next_item(label);
+
+        let [x, y] = self.value_position();
+        text::draw_centered(
+            if *value { "ON" } else { "OFF" },
+            [x + SLIDER_WIDTH / 2.0, y],
+            TEXT_SIZE,
+            foreground_color(focused),
+        );
+
+        if focused && (self.activated || self.direction != 0) {
+            *value ^= true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the value was changed this frame
+    pub fn slider(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+        step: f32,
+    ) -> bool {
+        let focused = self.next_item(label);
+
+        let fraction = (*value - range.start()) / (range.end() - range.start());
+        let [x, y] = self.value_position();
+        let color = foreground_color(focused);
+
+        shapes::draw_rectangle(x, y - 0.0625, SLIDER_WIDTH, 0.125, color);
+        shapes::draw_rectangle(
+            x + fraction.clamp(0.0, 1.0) * SLIDER_WIDTH - 0.125,
+            y - 0.25,
+            0.25,
+            0.5,
+            color,
+        );
+
+        if focused && self.direction != 0 {
+            let old_value = *value;
+            *value = (*value + self.direction as f32 * step).clamp(*range.start(), *range.end());
+
+            *value != old_value
+        } else {
+            false
+        }
+    }
+
+    pub fn end(self) {
+        self.state.num_items = self.index;
+
+        if self.state.num_items > 0 {
+            self.state.focus %= self.state.num_items;
+        }
+    }
+
+    /// Draws the row for the next item and its label, returning whether it has focus
+    fn next_item(&mut self, label: &str) -> bool {
+        let focused = self.index == self.state.focus;
+        let y = self.row_center();
+
+        if focused {
+            shapes::draw_rectangle(
+                -ROW_WIDTH / 2.0,
+                y - ROW_HEIGHT / 2.0,
+                ROW_WIDTH,
+                ROW_HEIGHT,
+                colors::WHITE,
+            );
+        }
+
+        text::draw_left_aligned(
+            label,
+            [-ROW_WIDTH / 2.0 + ROW_PADDING, y],
+            TEXT_SIZE,
+            foreground_color(focused),
+        );
+
+        self.index += 1;
+
+        focused
+    }
+
+    fn row_center(&self) -> f32 {
+        self.top - (self.index as f32 + 0.5) * ROW_HEIGHT
+    }
+
+    /// The left center of the area to the right of a row where its value is shown
+    fn value_position(&self) -> [f32; 2] {
+        [
+            ROW_WIDTH / 2.0 - ROW_PADDING - SLIDER_WIDTH,
+            self.top - (self.index as f32 - 0.5) * ROW_HEIGHT,
+        ]
+    }
+}
+
+/// Draws a modal asking the player to confirm a destructive action, with cancelling focused by
+/// default. Returns `Some(true)` once it is confirmed and `Some(false)` once it is cancelled,
+/// either from the menu or by pressing escape.
+pub fn confirmation(
+    state: &mut MenuState,
+    keybinds: &Keybinds,
+    message: &str,
+    width: f32,
+    height: f32,
+) -> Option<bool> {
+    draw_backdrop(width, height);
+    text::draw_centered(message, [0.0, ROW_HEIGHT * 1.5], TEXT_SIZE, colors::WHITE);
+
+    let mut menu = Menu::begin(state, keybinds);
+
+    let cancelled = menu.button("CANCEL");
+    let confirmed = menu.button("CONFIRM");
+
+    menu.end();
+
+    if confirmed {
+        Some(true)
+    } else if cancelled || input::is_key_pressed(KeyCode::Escape) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Moves the focus around a grid of items, laid out in rows from the top left, with the same
+/// keys as menus. Returns `true` if the focused item was activated this frame.
+pub fn navigate_grid(
+    focus: &mut usize,
+    num_items: usize,
+    columns: usize,
+    keybinds: &Keybinds,
+) -> bool {
+    if num_items == 0 {
+        return false;
+    }
+
+    let x = *focus % columns;
+
+    if is_direction_pressed(keybinds, Action::Left) && x > 0 {
+        *focus -= 1;
+    }
+
+    if is_direction_pressed(keybinds, Action::Right) && x + 1 < columns && *focus + 1 < num_items {
+        *focus += 1;
+    }
+
+    if is_direction_pressed(keybinds, Action::Jump) && *focus >= columns {
+        *focus -= columns;
+    }
+
+    if is_direction_pressed(keybinds, Action::Invert) && *focus + columns < num_items {
+        *focus += columns;
+    }
+
+    is_any_key_pressed(&ACTIVATE_KEYS)
+}
+
+/// Darkens everything drawn so far so a menu can be drawn over it
+pub fn draw_backdrop(width: f32, height: f32) {
+    shapes::draw_rectangle(
+        -width / 2.0,
+        -height / 2.0,
+        width,
+        height,
+        Color {
+            a: 0.75,
+            ..colors::BLACK
+        },
+    );
+}
+
+fn foreground_color(focused: bool) -> Color {
+    if focused {
+        colors::BLACK
+    } else {
+        colors::WHITE
+    }
+}
+
+/// Returns `true` if one of the movement action's keys was pressed this frame, other than those
+/// which activate items, so that jumping with space doesn't also move the focus up
+fn is_direction_pressed(keybinds: &Keybinds, action: Action) -> bool {
+    keybinds
+        .bindings(action)
+        .iter()
+        .any(|binding| !ACTIVATE_KEYS.contains(&binding.key) && binding.is_pressed())
+}
+
+fn is_any_key_pressed(keys: &[KeyCode]) -> bool {
+    keys.iter().any(|key| input::is_key_pressed(*key))
+}
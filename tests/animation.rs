@@ -0,0 +1,68 @@
+use std::f32::consts::TAU;
+
+use inverse::animation::{AnimationClocks, Easing, Timeline};
+
+#[test]
+fn timelines_reach_each_keyframe() {
+    let timeline =
+        Timeline::new(0.0)
+            .then(1.0, 4.0, Easing::Linear)
+            .then(2.0, 2.0, Easing::SineInOut);
+
+    assert_eq!(timeline.duration(), 3.0);
+    assert_eq!(timeline.sample(0.0), 0.0);
+    assert_eq!(timeline.sample(0.5), 2.0);
+    assert_eq!(timeline.sample(1.0), 4.0);
+    assert!((timeline.sample(2.0) - 3.0).abs() < 1e-6);
+    assert_eq!(timeline.sample(3.0), 2.0);
+}
+
+#[test]
+fn timelines_hold_their_ends_unless_they_loop() {
+    let timeline = Timeline::new(1.0).then(2.0, 3.0, Easing::Linear);
+
+    assert_eq!(timeline.sample(-1.0), 1.0);
+    assert_eq!(timeline.sample(5.0), 3.0);
+    assert_eq!(timeline.clone().looping().sample(5.0), 2.0);
+}
+
+#[test]
+fn eased_quarters_make_a_sine_wave() {
+    let wave = Timeline::new(0.0)
+        .then(1.0, 1.0, Easing::SineOut)
+        .then(2.0, -1.0, Easing::SineInOut)
+        .then(1.0, 0.0, Easing::SineIn)
+        .looping();
+
+    for i in 0..40 {
+        let time = i as f32 / 10.0;
+
+        assert!((wave.sample(time) - (time * TAU / 4.0).sin()).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn easings_keep_their_ends() {
+    for easing in [
+        Easing::Linear,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+    ] {
+        assert!(easing.apply(0.0).abs() < 1e-6);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn animation_clocks_run_separately_and_wrap() {
+    let mut clocks = AnimationClocks::new(4.0);
+
+    clocks.advance("first", 1.5);
+    clocks.advance("first", 3.0);
+    clocks.advance("second", 1.0);
+
+    assert!((clocks.time(&"first") - 0.5).abs() < 1e-6);
+    assert!((clocks.time(&"second") - 1.0).abs() < 1e-6);
+    assert_eq!(clocks.time(&"third"), 0.0);
+}
@@ -0,0 +1,55 @@
+use inverse::changelog::Changelog;
+
+const TEXT: &str = "# A comment
+[0.3.0]
+Newest change
+
+[0.2.0]
+First change
+Second change
+[0.1.0]
+";
+
+#[test]
+fn changelogs_are_read_newest_first() {
+    let changelog = TEXT.parse::<Changelog>().unwrap();
+
+    let versions = changelog
+        .releases
+        .iter()
+        .map(|release| release.version.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(versions, ["0.3.0", "0.2.0", "0.1.0"]);
+    assert_eq!(
+        changelog.releases[1].changes,
+        ["First change", "Second change"]
+    );
+}
+
+#[test]
+fn only_newer_releases_are_new() {
+    let changelog = TEXT.parse::<Changelog>().unwrap();
+
+    assert_eq!(changelog.since("0.2.0").len(), 1);
+    assert!(changelog.since("0.3.0").is_empty());
+    assert_eq!(changelog.since("unknown").len(), 3);
+}
+
+#[test]
+fn changes_must_follow_a_version() {
+    assert!("A change\n[0.1.0]".parse::<Changelog>().is_err());
+}
+
+#[test]
+fn the_bundled_changelog_is_valid() {
+    let changelog = include_str!("../changelog.txt")
+        .parse::<Changelog>()
+        .unwrap();
+
+    assert_eq!(
+        changelog.releases[0].version,
+        env!("CARGO_PKG_VERSION"),
+        "the newest release should be the current version"
+    );
+}
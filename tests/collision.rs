@@ -0,0 +1,548 @@
+use inverse::level::Levels;
+use inverse::physics::PhysicsConfig;
+use inverse::pickup::Pickup;
+use inverse::player::Player;
+use inverse::tile::Tile;
+
+/// Updates the player until it stops moving vertically, or panics if it never does
+fn settle(player: &mut Player, levels: &mut Levels) {
+    for _ in 0..1000 {
+        player.update(levels);
+
+        if player.on_ground {
+            return;
+        }
+    }
+
+    panic!("player never landed");
+}
+
+#[test]
+fn player_lands_on_the_bottom_of_an_empty_level() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    settle(&mut player, &mut levels);
+
+    assert_eq!(player.position[1], Player::SIZE / 2.0);
+    assert_eq!(player.velocity[1], 0.0);
+}
+
+#[test]
+fn player_lands_on_a_solid_tile() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::Solid;
+
+    settle(&mut player, &mut levels);
+
+    assert_eq!(player.position[1], 3.0 + Player::SIZE / 2.0);
+}
+
+#[test]
+fn inverted_player_lands_on_the_top_of_a_solid_level() {
+    let mut levels = Levels::new();
+    levels.tiles.fill(Tile::Solid);
+
+    let mut player = Player::new(false);
+    player.air_kind = true;
+
+    settle(&mut player, &mut levels);
+
+    assert_eq!(
+        player.position[1],
+        inverse::LOGICAL_SCREEN_HEIGHT - Player::SIZE / 2.0,
+    );
+}
+
+#[test]
+fn walls_stop_horizontal_movement() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x + 1, y]] = Tile::Solid;
+
+    assert_eq!(player.move_by(&levels, [1.0, 0.0]), Some(true));
+    assert_eq!(player.position[0], (x + 1) as f32 - Player::SIZE / 2.0);
+}
+
+#[test]
+fn moving_through_air_does_not_collide() {
+    let levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let start = player.position;
+
+    assert_eq!(player.move_by(&levels, [0.25, 0.25]), Some(false));
+    assert_eq!(player.position, [start[0] + 0.25, start[1] + 0.25]);
+}
+
+#[test]
+fn player_intersects_tiles_of_the_opposite_kind() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    assert!(!player.is_intersecting(&levels));
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y]] = Tile::Solid;
+
+    assert!(player.is_intersecting(&levels));
+}
+
+#[test]
+fn player_lands_on_a_one_way_tile() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::OneWay;
+
+    settle(&mut player, &mut levels);
+
+    assert_eq!(player.position[1], 3.0 + Player::SIZE / 2.0);
+}
+
+#[test]
+fn player_passes_up_through_a_one_way_tile() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y + 1]] = Tile::OneWay;
+
+    assert_eq!(player.move_by(&levels, [0.0, 1.0]), Some(false));
+    assert!(!player.is_intersecting(&levels));
+}
+
+#[test]
+fn inverted_player_lands_on_the_bottom_of_a_one_way_tile() {
+    let mut levels = Levels::new();
+    levels.tiles.fill(Tile::Solid);
+
+    let mut player = Player::new(false);
+    player.air_kind = true;
+
+    let x = player.position[0] as usize;
+    levels[[x, 8]] = Tile::OneWay;
+
+    settle(&mut player, &mut levels);
+
+    assert_eq!(player.position[1], 8.0 - Player::SIZE / 2.0);
+}
+
+#[test]
+fn inverted_player_passes_down_through_a_one_way_tile() {
+    let mut levels = Levels::new();
+    levels.tiles.fill(Tile::Solid);
+
+    let mut player = Player::new(false);
+    player.air_kind = true;
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y - 1]] = Tile::OneWay;
+
+    assert_eq!(player.move_by(&levels, [0.0, -1.0]), Some(false));
+    assert!(!player.is_intersecting(&levels));
+}
+
+#[test]
+fn spikes_kill_the_player_and_send_them_back_to_their_spawn_point() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let spawn_point = player.spawn_point;
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::Spike;
+
+    for _ in 0..1000 {
+        player.update(&mut levels);
+
+        if player.is_dead() {
+            break;
+        }
+    }
+
+    assert_eq!(player.deaths, 1);
+
+    let death_position = player.position;
+
+    for _ in 1..Player::DEATH_FRAMES {
+        player.update(&mut levels);
+        assert_eq!(player.position, death_position);
+    }
+
+    player.update(&mut levels);
+
+    assert!(!player.is_dead());
+    assert_eq!(player.position, spawn_point.position);
+}
+
+#[test]
+fn invincible_players_land_on_spikes() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+    player.debug_flags.invincible = true;
+
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::Spike;
+
+    settle(&mut player, &mut levels);
+
+    assert!(!player.is_dead());
+    assert_eq!(player.position[1], 3.0 + Player::SIZE / 2.0);
+}
+
+#[test]
+fn noclip_flies_through_tiles() {
+    let mut levels = Levels::new();
+    levels.tiles.fill(Tile::Solid);
+
+    let mut player = Player::new(false);
+    player.debug_flags.noclip = true;
+
+    let start = player.position;
+
+    player.fly_speed = Player::UPDATES_PER_SECOND;
+
+    player.inputs_down[1] = true;
+    player.update(&mut levels);
+
+    assert_eq!(player.position, [start[0] - 1.0, start[1]]);
+
+    // Without input the player stays still instead of falling
+    player.update(&mut levels);
+
+    assert_eq!(player.position, [start[0] - 1.0, start[1]]);
+}
+
+#[test]
+fn checkpoints_are_kept_after_leaving_their_level() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+
+    let x = player.position[0] as usize;
+    levels[[x, 3]] = Tile::Checkpoint;
+
+    settle(&mut player, &mut levels);
+
+    levels.next_level();
+    player.update(&mut levels);
+    levels.previous_level();
+    player.respawn(&levels);
+
+    assert_eq!(player.position, [x as f32 + 0.5, 3.5]);
+    assert!(!player.air_kind);
+}
+
+#[test]
+fn physics_overrides_only_apply_in_their_level() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+    levels.metadata[1].physics.gravity_scale = Some(0.5);
+
+    let mut player = Player::new(false);
+
+    player.update(&mut levels);
+    assert_eq!(player.physics, PhysicsConfig::DEFAULT);
+
+    levels.next_level();
+    player.update(&mut levels);
+    assert_eq!(player.physics.gravity, PhysicsConfig::DEFAULT.gravity * 0.5);
+
+    levels.previous_level();
+    player.update(&mut levels);
+    assert_eq!(player.physics, PhysicsConfig::DEFAULT);
+}
+
+#[test]
+fn curses_mirror_horizontal_controls() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y]] = Tile::Curse;
+
+    player.update(&mut levels);
+    assert!(player.is_cursed());
+
+    player.inputs_down[3] = true;
+    player.update(&mut levels);
+
+    assert!(player.velocity[0] < 0.0);
+}
+
+#[test]
+fn double_jumps_give_one_jump_in_the_air() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y]] = Tile::DoubleJump;
+
+    player.update(&mut levels);
+    assert_eq!(player.jumps_remaining, 1);
+
+    player.inputs_down[0] = true;
+    player.inputs_ready[0] = true;
+    player.update(&mut levels);
+
+    let jump_velocity = player.velocity[1];
+    assert!(jump_velocity > 0.0);
+    assert_eq!(player.jumps_remaining, 0);
+
+    levels[[x, y]] = Tile::Empty;
+
+    player.inputs_down[0] = true;
+    player.inputs_ready[0] = true;
+    player.update(&mut levels);
+
+    assert!(player.velocity[1] < jump_velocity);
+
+    // Landing gives the jump back
+    settle(&mut player, &mut levels);
+    assert_eq!(player.jumps_remaining, 1);
+}
+
+#[test]
+fn double_jumps_are_left_behind_with_their_level() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+
+    let [x, y] = player.position.map(|position| position as usize);
+    levels[[x, y]] = Tile::DoubleJump;
+
+    player.update(&mut levels);
+    assert_eq!(player.jumps_remaining, 1);
+
+    player.position[0] = inverse::LOGICAL_SCREEN_WIDTH - Player::SIZE / 2.0;
+    player.velocity[0] = 0.5;
+    player.update(&mut levels);
+
+    assert_eq!(levels.level_index, 1);
+    assert_eq!(player.air_jumps, 0);
+    assert_eq!(player.jumps_remaining, 0);
+}
+
+#[test]
+fn switches_flip_their_region_until_the_levels_are_written() {
+    let mut levels = Levels::new();
+    levels.metadata[0].switch_region = Some([[0, 8], [1, 9]]);
+
+    let mut player = Player::new(false);
+
+    let x = player.position[0] as usize;
+    levels[[x, 3]] = Tile::Switch;
+    levels[[0, 9]] = Tile::Solid;
+
+    let text = levels.to_string();
+
+    settle(&mut player, &mut levels);
+
+    assert!(levels.switched.contains(&0));
+    assert_eq!(levels[[0, 8]], Tile::Solid);
+    assert_eq!(levels[[0, 9]], Tile::Empty);
+    assert_eq!(levels.to_string(), text);
+}
+
+#[test]
+fn switches_push_the_player_out_of_flipped_tiles() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    // The player is still partly in the tile above the switch when they pass into it
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::Switch;
+    levels.metadata[0].switch_region = Some([[x, 3], [x, 3]]);
+
+    settle(&mut player, &mut levels);
+
+    assert!(!levels.switched.is_empty());
+    assert_eq!(levels[[x, 3]], Tile::Solid);
+    assert!(!player.is_dead());
+    assert!(!player.is_intersecting(&levels));
+}
+
+#[test]
+fn coins_are_collected_by_passing_through_them() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    let x = player.position[0] as usize;
+    levels[[x, 2]] = Tile::Coin;
+    levels[[x + 1, 0]] = Tile::Coin;
+
+    settle(&mut player, &mut levels);
+
+    let coin = levels.index_of([x, 2]).unwrap();
+
+    assert_eq!(player.coins, [coin].into());
+    assert_eq!(levels.coins(0).count(), 2);
+}
+
+#[test]
+fn levels_can_widen_the_radius_coins_are_collected_within() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    settle(&mut player, &mut levels);
+
+    let position = [player.position[0] as usize + 2, player.position[1] as usize];
+    levels[position] = Tile::Coin;
+
+    player.update(&mut levels);
+    assert!(player.coins.is_empty());
+
+    levels.metadata[0].physics.pickup_radii[Pickup::Coin as usize] = Some(3.0);
+
+    player.update(&mut levels);
+    assert_eq!(player.coins, [levels.index_of(position).unwrap()].into());
+}
+
+#[test]
+fn entering_a_level_finds_the_lowest_free_spot_on_its_left_edge() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+    player.enter_level(&mut levels, 1);
+
+    // Inserted levels have a floor 5 tiles deep
+    assert_eq!(levels.level_index, 1);
+    assert_eq!(
+        player.position,
+        [Player::SIZE / 2.0, 5.0 + Player::SIZE / 2.0]
+    );
+    assert_eq!(player.spawn_point.position, player.position);
+}
+
+#[test]
+fn player_is_drawn_between_their_last_two_positions() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+
+    player.update(&mut levels);
+    player.update(&mut levels);
+
+    let [x, y] = player.interpolated_position(0.5);
+
+    assert_eq!(x, player.position[0]);
+    assert_eq!(y, (player.previous_position[1] + player.position[1]) / 2.0);
+    assert!(y > player.position[1]);
+
+    // Respawning jumps straight to the spawn point instead of sliding there
+    player.respawn(&levels);
+
+    assert_eq!(
+        player.interpolated_position(0.5),
+        player.spawn_point.position
+    );
+}
+
+#[test]
+fn gems_are_picked_up_within_their_radius() {
+    let mut levels = Levels::new();
+    levels[[4, 2]] = Tile::Solid;
+    levels.full_gem = levels.index_of([4, 3]);
+
+    let mut player = Player::new(false);
+    let center = levels.gem_center(levels.full_gem.unwrap(), false).unwrap();
+    assert_eq!(center, [4.5, 3.5]);
+
+    player.position = [
+        center[0] + player.physics.pickup_radius(Pickup::Gem) * 0.9,
+        center[1],
+    ];
+    assert!(player.touches_pickup(Pickup::Gem, center));
+
+    player.position = [
+        center[0] + player.physics.pickup_radius(Pickup::Gem) * 1.1,
+        center[1],
+    ];
+    assert!(!player.touches_pickup(Pickup::Gem, center));
+
+    // Enabled gems sink into the tile they rest on
+    assert_eq!(
+        levels.gem_center(levels.full_gem.unwrap(), true),
+        Some([4.5, 2.5])
+    );
+}
+
+#[test]
+fn fast_players_do_not_pass_through_thin_walls() {
+    let mut levels = Levels::new();
+
+    for y in 0..Levels::LEVEL_HEIGHT {
+        levels[[8, y]] = Tile::Solid;
+    }
+
+    let mut player = Player::new(false);
+
+    for speed in [2.0, 4.0, 7.0] {
+        player.position = [6.5, 5.5];
+
+        assert_eq!(player.move_by(&levels, [speed, 0.0]), Some(true));
+        assert_eq!(player.position[0], 8.0 - Player::SIZE / 2.0);
+    }
+}
+
+#[test]
+fn fast_falls_land_on_thin_floors() {
+    let mut levels = Levels::new();
+
+    for x in 0..Levels::LEVEL_WIDTH - 1 {
+        levels[[x, 3]] = Tile::Solid;
+    }
+
+    let mut player = Player::new(false);
+    player.position = [3.5, 9.5];
+    player.velocity = [0.0, -50.0];
+
+    player.update(&mut levels);
+
+    assert!(player.on_ground);
+    assert_eq!(player.position[1], 4.0 + Player::SIZE / 2.0);
+}
+
+/// Jumps from standing at the given x position under a tile whose right edge is at x = 5, and
+/// returns the highest the player got
+fn jump_under_corner(x: f32) -> f32 {
+    let mut levels = Levels::new();
+
+    for x in 0..Levels::LEVEL_WIDTH - 1 {
+        levels[[x, 3]] = Tile::Solid;
+    }
+
+    levels[[4, 5]] = Tile::Solid;
+
+    let mut player = Player::new(false);
+    player.position = [x, 4.25];
+    settle(&mut player, &mut levels);
+
+    player.inputs_down[0] = true;
+    player.inputs_ready[0] = true;
+
+    (0..30)
+        .map(|_| {
+            player.update(&mut levels);
+            player.position[1]
+        })
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn jumps_which_clip_a_corner_are_nudged_past_it() {
+    assert!(jump_under_corner(5.0 + Player::SIZE / 2.0 - 0.0625) > 5.0);
+}
+
+#[test]
+fn jumps_which_hit_more_than_a_corner_are_stopped() {
+    assert!(jump_under_corner(5.0 + Player::SIZE / 2.0 - 0.25) < 5.0);
+}
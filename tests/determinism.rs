@@ -0,0 +1,77 @@
+use inverse::level::Levels;
+use inverse::player::Player;
+use inverse::tile::Tile;
+use inverse::timestep::FixedTimestep;
+
+/// Runs the player through a level with a canned input script, returning their position after
+/// every tick
+fn simulate(ticks: usize) -> Vec<[f32; 2]> {
+    let mut levels = Levels::new();
+
+    for x in 0..Levels::LEVEL_WIDTH - 1 {
+        levels[[x, 3]] = Tile::Solid;
+    }
+
+    levels[[6, 4]] = Tile::Solid;
+    levels[[9, 5]] = Tile::Solid;
+
+    let mut player = Player::new(false);
+
+    (0..ticks)
+        .map(|tick| {
+            // Hold right, jumping every so often and inverting now and then
+            player.inputs_down = [tick % 40 < 10, false, tick % 90 < 5, tick % 200 < 150];
+            player.inputs_ready = player.inputs_down;
+
+            player.update(&mut levels);
+
+            player.position
+        })
+        .collect()
+}
+
+#[test]
+fn the_same_inputs_give_the_same_positions() {
+    let first = simulate(600);
+    let second = simulate(600);
+
+    for (tick, (first, second)) in first.iter().zip(&second).enumerate() {
+        assert_eq!(
+            first.map(f32::to_bits),
+            second.map(f32::to_bits),
+            "diverged on tick {tick}"
+        );
+    }
+}
+
+#[test]
+fn ticks_do_not_depend_on_the_frame_rate() {
+    for frames_per_second in [30, 60, 144, 240] {
+        let mut timestep = FixedTimestep::default();
+
+        for _ in 0..frames_per_second * 10 {
+            timestep.advance(1.0 / frames_per_second as f32, 1.0);
+        }
+
+        let expected_ticks = 10 * Player::UPDATES_PER_SECOND as u64;
+
+        assert!(
+            timestep.tick.abs_diff(expected_ticks) <= 1,
+            "{} ticks at {frames_per_second} fps",
+            timestep.tick
+        );
+    }
+}
+
+#[test]
+fn long_frames_are_capped() {
+    let mut timestep = FixedTimestep::default();
+
+    assert_eq!(
+        timestep.advance(1.0, 1.0),
+        Player::MAXIMUM_UPDATES_PER_FRAME
+    );
+
+    // The rest of the long frame is dropped instead of being caught up on
+    assert!(timestep.advance(0.0, 1.0) <= 1);
+}
@@ -0,0 +1,354 @@
+use inverse::editor::{self, Clipboard, Drag, Editor, Gem, Shape};
+use inverse::level::Levels;
+use inverse::player::{Player, SpawnPoint};
+use inverse::tile::Tile;
+
+fn drag(start: [usize; 2], end: [usize; 2], shape: Shape, constrain: bool) -> Drag {
+    let mut drag = Drag {
+        start,
+        end: start,
+        shape,
+    };
+    drag.set_end(end, constrain);
+
+    drag
+}
+
+#[test]
+fn rectangles_cover_every_tile_between_their_corners() {
+    let positions = drag([3, 1], [1, 2], Shape::Rectangle, false).positions();
+
+    assert_eq!(positions.len(), 6);
+    assert!(positions.contains(&[1, 1]));
+    assert!(positions.contains(&[3, 2]));
+}
+
+#[test]
+fn constrained_rectangles_are_square() {
+    let drag = drag([0, 0], [3, 1], Shape::Rectangle, true);
+
+    assert_eq!(drag.end, [3, 3]);
+}
+
+#[test]
+fn lines_cover_one_tile_per_step() {
+    let positions = drag([0, 0], [4, 2], Shape::Line, false).positions();
+
+    assert_eq!(positions, [[0, 0], [1, 1], [2, 1], [3, 2], [4, 2]]);
+}
+
+#[test]
+fn constrained_lines_snap_to_an_axis() {
+    let drag = drag([2, 2], [8, 3], Shape::Line, true);
+
+    assert_eq!(drag.end, [8, 2]);
+}
+
+#[test]
+fn finishing_a_drag_fills_the_shape() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+
+    editor.start_drag([0, 0], Shape::Rectangle);
+
+    if let Editor::Full { drag: Some(drag) } = &mut editor {
+        drag.set_end([2, 1], false);
+    }
+
+    assert!(editor.finish_drag(&mut levels, &mut player));
+    assert_eq!(levels[[2, 1]], Tile::Solid);
+    assert_eq!(levels[[3, 1]], Tile::Empty);
+}
+
+#[test]
+fn drags_which_leave_the_player_no_room_crush_them() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+
+    editor.start_drag([0, 0], Shape::Rectangle);
+
+    if let Editor::Full { drag: Some(drag) } = &mut editor {
+        drag.set_end([Levels::LEVEL_WIDTH - 1, Levels::LEVEL_HEIGHT - 1], false);
+    }
+
+    assert!(editor.finish_drag(&mut levels, &mut player));
+    assert!(player.is_dead());
+}
+
+#[test]
+fn tiles_placed_on_the_player_push_them_out() {
+    let mut levels = Levels::new();
+    let mut player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+
+    let position = player.position.map(|coordinate| coordinate as usize);
+    let tile_index = levels.index_of(position).unwrap();
+
+    assert!(editor.place_tile(tile_index, Tile::Solid, &mut levels, &mut player));
+    assert_eq!(levels.tiles[tile_index], Tile::Solid);
+    assert!(!player.is_dead());
+    assert!(!player.is_intersecting(&levels));
+}
+
+#[test]
+fn restarting_undoes_temporary_tiles_and_keeps_the_editor() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+    let mut editor = Editor::Limited {
+        last_selected: None,
+    };
+
+    let tile_index = levels.index_of([2, 8]).unwrap();
+    let _ = editor.toggle_tile_index(tile_index, &mut levels, &mut player);
+    assert_eq!(levels.tiles[tile_index], Tile::Empty.toggled());
+
+    player.position = [3.5, 9.5];
+    player.velocity = [0.5, 0.5];
+    player.checkpoints.insert(
+        0,
+        SpawnPoint {
+            position: [1.5, 1.5],
+            air_kind: true,
+        },
+    );
+
+    editor.restart_level(&mut levels, &mut player);
+
+    assert_eq!(levels.tiles[tile_index], Tile::Empty);
+    assert!(editor.is_limited());
+    assert!(player.checkpoints.is_empty());
+    assert_eq!(player.position, player.spawn_point.position);
+    assert_eq!(player.velocity, [0.0, 0.0]);
+}
+
+#[test]
+fn selections_are_copied_and_pasted_into_other_levels() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+    let mut clipboard = Clipboard::default();
+
+    levels[[5, 2]] = Tile::Spike;
+    levels[[6, 3]] = Tile::Coin;
+
+    editor.start_drag([5, 2], Shape::Selection);
+    if let Editor::Full { drag: Some(drag) } = &mut editor {
+        drag.set_end([6, 3], false);
+    }
+
+    clipboard.selection = editor.finish_selection();
+    assert!(!editor.finish_drag(&mut levels, &mut player));
+    assert!(clipboard.copy(&levels));
+    assert_eq!(levels[[5, 2]], Tile::Spike);
+
+    levels.level_index = 1;
+    levels.update_level_offset();
+
+    assert!(clipboard.start_pasting());
+    assert!(editor.paste([8, 6], &mut clipboard, &mut levels, &mut player));
+    assert!(!clipboard.pasting);
+
+    assert_eq!(levels[[8, 6]], Tile::Spike);
+    assert_eq!(levels[[9, 7]], Tile::Coin);
+    assert_eq!(levels[[8, 7]], Tile::Empty);
+}
+
+#[test]
+fn pastes_leave_out_tiles_off_the_screen() {
+    let mut clipboard = Clipboard {
+        selection: Some(drag([0, 0], [2, 2], Shape::Selection, false)),
+        ..Default::default()
+    };
+
+    assert!(clipboard.copy(&Levels::new()));
+    assert!(clipboard.start_pasting());
+
+    let corner = [Levels::LEVEL_WIDTH - 1, Levels::LEVEL_HEIGHT - 2];
+
+    assert_eq!(
+        clipboard
+            .paste_positions(corner)
+            .into_iter()
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>(),
+        [corner, [corner[0], corner[1] + 1]],
+    );
+}
+
+/// Levels which can each be told apart by the solid tile in their bottom left corner
+fn numbered_levels(num_levels: usize) -> Levels {
+    let mut levels = Levels::new();
+
+    for level_index in 1..num_levels {
+        levels.insert_level(level_index);
+    }
+
+    levels.tiles.fill(Tile::Empty);
+
+    for level_index in 0..num_levels {
+        levels.level_index = level_index;
+        levels.update_level_offset();
+        levels[[0, level_index]] = Tile::Solid;
+        levels.metadata[level_index].name = Some(format!("Level {level_index}"));
+    }
+
+    levels.level_index = 0;
+    levels.update_level_offset();
+
+    levels
+}
+
+fn level_order(levels: &Levels) -> Vec<usize> {
+    let column = (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT;
+
+    levels
+        .tiles
+        .chunks(column)
+        .map(|level| level.iter().position(|tile| *tile == Tile::Solid).unwrap())
+        .collect()
+}
+
+#[test]
+fn moving_levels_keeps_the_current_level_and_gems_with_them() {
+    let mut levels = numbered_levels(4);
+    levels.level_index = 2;
+    levels.update_level_offset();
+
+    let gem_in_level_3 = 3 * (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT + 12;
+    levels.limited_gem = Some(gem_in_level_3);
+
+    levels.move_level(3, 0);
+
+    assert_eq!(level_order(&levels), [3, 0, 1, 2]);
+    assert_eq!(levels.metadata[0].name.as_deref(), Some("Level 3"));
+    assert_eq!(levels.level_index, 3);
+    assert_eq!(levels.limited_gem, Some(12));
+
+    levels.move_level(0, 3);
+
+    assert_eq!(level_order(&levels), [0, 1, 2, 3]);
+    assert_eq!(levels.level_index, 2);
+    assert_eq!(levels.limited_gem, Some(gem_in_level_3));
+}
+
+#[test]
+fn inserting_and_removing_levels_keeps_the_gems_in_their_levels() {
+    let mut levels = numbered_levels(3);
+    levels.level_index = 1;
+    levels.update_level_offset();
+
+    let level_tiles = (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT;
+    levels.limited_gem = Some(level_tiles + 12);
+    levels.full_gem = Some(2 * level_tiles + 12);
+
+    levels.insert_level(1);
+
+    assert_eq!(level_order(&levels), [0, 0, 1, 2]);
+    assert_eq!(levels.level_index, 2);
+    assert_eq!(levels.limited_gem, Some(2 * level_tiles + 12));
+    assert_eq!(levels.full_gem, Some(3 * level_tiles + 12));
+
+    levels.remove_level(2);
+
+    assert_eq!(level_order(&levels), [0, 0, 2]);
+    assert_eq!(levels.level_index, 2);
+    assert_eq!(levels.limited_gem, None);
+    assert_eq!(levels.full_gem, Some(2 * level_tiles + 12));
+
+    levels.remove_level(2);
+
+    assert_eq!(levels.level_index, 1);
+    assert_eq!(levels.full_gem, None);
+}
+
+#[test]
+fn duplicated_levels_are_inserted_after_the_original() {
+    let mut levels = numbered_levels(3);
+    levels.level_index = 2;
+    levels.update_level_offset();
+
+    levels.duplicate_level(1);
+
+    assert_eq!(levels.num_levels, 4);
+    assert_eq!(level_order(&levels), [0, 1, 1, 2]);
+    assert_eq!(levels.metadata[2].name.as_deref(), Some("Level 1 copy"));
+    assert_eq!(levels.level_index, 3);
+    assert_eq!(levels.to_string().parse::<Levels>().unwrap().num_levels, 4);
+}
+
+#[test]
+fn gems_are_placed_moved_and_removed() {
+    let mut levels = numbered_levels(1);
+    levels[[2, 0]] = Tile::Solid;
+    levels[[3, 0]] = Tile::Solid;
+
+    let player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+
+    let first = levels.index_of([2, 1]).unwrap();
+    let second = levels.index_of([3, 1]).unwrap();
+
+    assert!(editor.place_gem(Gem::Limited, first, &mut levels, &player));
+    assert!(editor.place_gem(Gem::Full, second, &mut levels, &player));
+    assert_eq!(levels.limited_gem, Some(first));
+    assert_eq!(levels.full_gem, Some(second));
+
+    let reloaded = levels.to_string().parse::<Levels>().unwrap();
+    assert_eq!(reloaded.limited_gem, Some(first));
+    assert_eq!(reloaded.full_gem, Some(second));
+
+    assert!(editor.place_gem(Gem::Full, second, &mut levels, &player));
+    assert!(editor.place_gem(Gem::Limited, second, &mut levels, &player));
+    assert_eq!(levels.limited_gem, Some(second));
+    assert_eq!(levels.full_gem, None);
+}
+
+#[test]
+fn gems_must_rest_on_solid_tiles_away_from_the_player() {
+    let mut levels = numbered_levels(1);
+    levels[[2, 0]] = Tile::Solid;
+    levels[[2, 1]] = Tile::Solid;
+    levels[[3, 0]] = Tile::Solid;
+    levels[[7, 4]] = Tile::Solid;
+
+    let player = Player::new(false);
+    let mut editor = Editor::Full { drag: None };
+
+    let on_floor = levels.index_of([3, 1]).unwrap();
+    levels.full_gem = Some(on_floor);
+
+    for (position, reason) in [
+        ([4, 1], "nothing below"),
+        ([2, 1], "solid tile"),
+        ([3, 1], "other gem"),
+        ([3, 0], "bottom row"),
+        ([7, 5], "touching the player"),
+    ] {
+        let tile_index = levels.index_of(position).unwrap();
+
+        assert!(
+            !editor::can_place_gem(tile_index, &levels, &player),
+            "{reason}"
+        );
+        assert!(
+            !editor.place_gem(Gem::Limited, tile_index, &mut levels, &player),
+            "{reason}",
+        );
+    }
+
+    assert_eq!(levels.limited_gem, None);
+
+    let mut limited_editor = Editor::Limited {
+        last_selected: None,
+    };
+    let free = levels.index_of([2, 2]).unwrap();
+
+    assert!(editor::can_place_gem(free, &levels, &player));
+    assert!(!limited_editor.place_gem(Gem::Limited, free, &mut levels, &player));
+}
@@ -0,0 +1,50 @@
+use inverse::input_script::InputScript;
+use inverse::level::Levels;
+use inverse::player::Player;
+use inverse::tile::Tile;
+
+#[test]
+fn steps_hold_their_actions_for_their_ticks() {
+    let script = "right 30 ticks; jump; wait 10\nleft+swap 2"
+        .parse::<InputScript>()
+        .unwrap();
+
+    assert_eq!(script.len(), 43);
+
+    let inputs = script.inputs().collect::<Vec<_>>();
+
+    assert_eq!(inputs[0].down, [false, false, false, true]);
+    assert!(inputs[0].pressed);
+    assert!(!inputs[29].pressed);
+    assert_eq!(inputs[30].down, [true, false, false, false]);
+    assert_eq!(inputs[31].down, [false; 4]);
+    assert_eq!(inputs[41].down, [false, true, true, false]);
+}
+
+#[test]
+fn invalid_steps_are_rejected() {
+    assert!("walk 3".parse::<InputScript>().is_err());
+    assert!("right three".parse::<InputScript>().is_err());
+    assert!("right 3 seconds".parse::<InputScript>().is_err());
+}
+
+#[test]
+fn scripts_drive_the_player() {
+    let mut levels = Levels::new();
+
+    for x in 0..Levels::LEVEL_WIDTH - 1 {
+        levels[[x, 3]] = Tile::Solid;
+    }
+
+    let mut player = Player::new(false);
+    let script = "wait 60; right 30; jump".parse::<InputScript>().unwrap();
+    let start = player.position;
+
+    for input in script.inputs() {
+        input.apply(&mut player);
+        player.update(&mut levels);
+    }
+
+    assert!(player.position[0] > start[0]);
+    assert!(player.velocity[1] > 0.0);
+}
@@ -0,0 +1,165 @@
+use inverse::keybinds::{Action, Binding, Keybinds, Modifiers, Preset};
+use macroquad::input::KeyCode;
+
+#[test]
+fn default_keybinds_round_trip() {
+    let keybinds = Keybinds::default();
+
+    assert_eq!(keybinds.to_string().parse::<Keybinds>().unwrap(), keybinds);
+}
+
+#[test]
+fn missing_actions_keep_their_default_keys() {
+    let keybinds = "jump = K\n".parse::<Keybinds>().unwrap();
+
+    assert_eq!(keybinds.bindings(Action::Jump), [Binding::from(KeyCode::K)]);
+    assert_eq!(
+        keybinds.bindings(Action::Left),
+        Keybinds::default().bindings(Action::Left),
+    );
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    assert!("jump = Banana\n".parse::<Keybinds>().is_err());
+    assert!("fly = W\n".parse::<Keybinds>().is_err());
+}
+
+#[test]
+fn toggling_a_key_binds_and_unbinds_it() {
+    let mut keybinds = Keybinds::default();
+
+    assert!(keybinds.toggle_binding(Action::Jump, KeyCode::K));
+    assert!(keybinds.bindings(Action::Jump).contains(&KeyCode::K.into()));
+
+    assert!(keybinds.toggle_binding(Action::Jump, KeyCode::K));
+    assert!(!keybinds.bindings(Action::Jump).contains(&KeyCode::K.into()));
+
+    assert!(!keybinds.toggle_binding(Action::Jump, KeyCode::Escape));
+}
+
+#[test]
+fn actions_are_written_with_their_context() {
+    let text = Keybinds::default().to_string();
+
+    assert!(text.contains("gameplay.jump = W Up Space\n"));
+    assert!(text.contains("editor.copy = Ctrl+C\n"));
+    assert!(text.contains("global.fullscreen = F11\n"));
+
+    assert!("editor.jump = K\n".parse::<Keybinds>().is_err());
+}
+
+#[test]
+fn chords_round_trip() {
+    let keybinds = "editor.save_levels = Ctrl+Shift+S F5\n"
+        .parse::<Keybinds>()
+        .unwrap();
+
+    assert_eq!(
+        keybinds.bindings(Action::SaveLevels),
+        [
+            Binding {
+                modifiers: Modifiers {
+                    shift: true,
+                    control: true,
+                    alt: false,
+                },
+                key: KeyCode::S,
+            },
+            Binding::from(KeyCode::F5),
+        ],
+    );
+    assert_eq!(keybinds.to_string().parse::<Keybinds>().unwrap(), keybinds);
+    assert!("editor.copy = Super+C\n".parse::<Keybinds>().is_err());
+}
+
+#[test]
+fn default_keybinds_have_no_conflicts() {
+    let keybinds = Keybinds::default();
+
+    for action in Action::ALL {
+        assert_eq!(keybinds.conflicts(action), [], "{action:?}");
+    }
+}
+
+#[test]
+fn conflicts_are_found_across_contexts() {
+    let mut keybinds = Keybinds::default();
+
+    // Holding control while pressing S would save the levels and invert the player
+    assert!(keybinds.toggle_binding(
+        Action::SaveLevels,
+        Binding {
+            modifiers: Modifiers::CONTROL,
+            key: KeyCode::S,
+        },
+    ));
+
+    assert_eq!(keybinds.conflicts(Action::SaveLevels), [Action::Invert]);
+    assert_eq!(keybinds.conflicts(Action::Invert), [Action::SaveLevels]);
+
+    // Chords with different modifiers don't trigger each other
+    assert!(keybinds.toggle_binding(
+        Action::Paste,
+        Binding {
+            modifiers: Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+            key: KeyCode::C,
+        },
+    ));
+
+    assert_eq!(keybinds.conflicts(Action::Paste), []);
+}
+
+#[test]
+fn level_list_keys_only_conflict_with_global_ones() {
+    let mut keybinds = Keybinds::default();
+
+    // The level list pauses the game, so it can share keys with the player's movement
+    assert!(
+        keybinds
+            .bindings(Action::Jump)
+            .contains(&KeyCode::Up.into())
+    );
+    assert_eq!(keybinds.conflicts(Action::ListUp), []);
+
+    assert!(keybinds.toggle_binding(Action::DuplicateLevel, KeyCode::F11));
+    assert_eq!(
+        keybinds.conflicts(Action::DuplicateLevel),
+        [Action::Fullscreen],
+    );
+}
+
+#[test]
+fn presets_are_recognised_once_applied() {
+    let mut keybinds = Keybinds::default();
+    assert_eq!(keybinds.preset(), Some(Preset::Qwerty));
+
+    for preset in Preset::ALL {
+        keybinds.apply_preset(preset);
+
+        assert_eq!(keybinds.preset(), Some(preset));
+        assert_eq!(
+            keybinds.bindings(Action::Copy),
+            Keybinds::default().bindings(Action::Copy),
+        );
+
+        for action in Action::ALL {
+            assert_eq!(keybinds.conflicts(action), [], "{preset:?} {action:?}");
+        }
+    }
+
+    assert!(keybinds.toggle_binding(Action::Jump, KeyCode::Space));
+    assert_eq!(keybinds.preset(), None);
+}
+
+#[test]
+fn azerty_is_suggested_for_its_locales() {
+    assert_eq!(Preset::for_locale("fr_FR.UTF-8"), Some(Preset::Azerty));
+    assert_eq!(Preset::for_locale("nl_BE@euro"), Some(Preset::Azerty));
+    assert_eq!(Preset::for_locale("fr_CA.UTF-8"), None);
+    assert_eq!(Preset::for_locale("en_GB.UTF-8"), None);
+    assert_eq!(Preset::for_locale("C"), None);
+}
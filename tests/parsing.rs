@@ -0,0 +1,363 @@
+use inverse::level::{
+    LevelMetadata, LevelProblem, Levels, ParseLevelError, ParseWarning, TextPosition,
+};
+use inverse::tile::Tile;
+
+const ORIGINAL_LEVELS: &str = include_str!("../original_levels.txt");
+
+/// Builds a single level from its rows, top first, padding each row with air
+fn level_text(rows: &[&str]) -> String {
+    let mut text = String::new();
+
+    for y in 0..Levels::LEVEL_HEIGHT {
+        let row = rows.get(y).copied().unwrap_or("");
+        text.push_str(&format!(
+            "{row:<width$}|\n",
+            width = Levels::LEVEL_WIDTH - 1
+        ));
+    }
+
+    text
+}
+
+#[test]
+fn original_levels_parse() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+
+    assert_eq!(
+        levels.tiles.len(),
+        levels.num_levels * (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT,
+    );
+    assert!(levels.limited_gem.is_some());
+    assert!(levels.full_gem.is_some());
+}
+
+#[test]
+fn original_levels_round_trip() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+
+    assert_eq!(levels.to_string().parse::<Levels>().unwrap(), levels);
+}
+
+#[test]
+fn headerless_levels_are_written_with_a_version() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+
+    assert_eq!(
+        levels.to_string(),
+        format!("#version {}\n{ORIGINAL_LEVELS}", Levels::FORMAT_VERSION),
+    );
+}
+
+#[test]
+fn metadata_round_trips() {
+    let text = format!(
+        "#version {}\n#level 1\n#name First steps\n#author Someone\n#par_time 12.5\n{}",
+        Levels::FORMAT_VERSION,
+        level_text(&[]),
+    );
+
+    let levels = text.parse::<Levels>().unwrap();
+
+    assert_eq!(
+        levels.metadata,
+        [LevelMetadata {
+            name: Some("First steps".to_owned()),
+            author: Some("Someone".to_owned()),
+            par_time: Some(12.5),
+            ..Default::default()
+        }],
+    );
+    assert_eq!(levels.to_string(), text);
+}
+
+#[test]
+fn metadata_for_missing_levels_is_rejected() {
+    let text = format!("#version 2\n#level 2\n#name Nowhere\n{}", level_text(&[]));
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::MetadataForMissingLevel(2)),
+    ));
+}
+
+#[test]
+fn huge_level_numbers_are_rejected_where_they_are() {
+    let text = format!(
+        "#version 2\n#level 1\n#level 99999999999\n#name Far away\n{}",
+        level_text(&[])
+    );
+
+    let error = Levels::parse_located(&text).unwrap_err();
+
+    assert!(matches!(
+        error.error,
+        ParseLevelError::MetadataForMissingLevel(99999999999),
+    ));
+    assert_eq!(error.position, TextPosition { line: 3, column: 1 });
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let text = format!("#version 99\n{}", level_text(&[]));
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::UnsupportedVersion(99)),
+    ));
+}
+
+#[test]
+fn headers_must_start_with_the_version() {
+    let text = format!("#level 1\n#version 2\n{}", level_text(&[]));
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::InvalidHeaderLine(0)),
+    ));
+}
+
+#[test]
+fn empty_level_parses() {
+    let levels = level_text(&[]).parse::<Levels>().unwrap();
+
+    assert_eq!(levels, Levels::new());
+}
+
+#[test]
+fn rows_are_read_from_the_bottom() {
+    let levels = level_text(&["x"]).parse::<Levels>().unwrap();
+
+    assert_eq!(levels[[0, Levels::LEVEL_HEIGHT - 1]], Tile::Solid);
+    assert_eq!(levels[[0, 0]], Tile::Empty);
+}
+
+#[test]
+fn gems_are_parsed() {
+    let mut rows = [""; Levels::LEVEL_HEIGHT];
+    rows[Levels::LEVEL_HEIGHT - 2] = " e   E";
+    rows[Levels::LEVEL_HEIGHT - 1] = " x   x";
+
+    let levels = level_text(&rows).parse::<Levels>().unwrap();
+
+    assert_eq!(levels.limited_gem, Some(Levels::LEVEL_HEIGHT + 1));
+    assert_eq!(levels.full_gem, Some(5 * Levels::LEVEL_HEIGHT + 1));
+}
+
+#[test]
+fn invalid_height_is_rejected() {
+    let text = level_text(&[]);
+    let text = text.lines().skip(1).collect::<Vec<_>>().join("\n");
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::InvalidHeight),
+    ));
+}
+
+#[test]
+fn invalid_width_is_rejected() {
+    let text = level_text(&[]).replace(" |", "|");
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::InvalidWidth),
+    ));
+}
+
+#[test]
+fn invalid_tile_character_is_rejected() {
+    assert!(matches!(
+        level_text(&["q"]).parse::<Levels>(),
+        Err(ParseLevelError::InvalidTileCharacter('q')),
+    ));
+}
+
+#[test]
+fn errors_point_at_where_they_were_found() {
+    let text = format!("#version 5\n{}", level_text(&["", "", "   q"]));
+
+    let error = Levels::parse_located(&text).unwrap_err();
+
+    assert!(matches!(
+        error.error,
+        ParseLevelError::InvalidTileCharacter('q')
+    ));
+    assert_eq!(error.position, TextPosition { line: 4, column: 4 });
+    assert_eq!(error.to_string().lines().last(), Some("       ^"));
+}
+
+#[test]
+fn levels_without_an_entrance_are_problems() {
+    let mut levels = Levels::new();
+    assert!(levels.problems().is_empty());
+
+    for y in 0..Levels::LEVEL_HEIGHT {
+        levels[[0, y]] = Tile::Solid;
+    }
+
+    assert_eq!(levels.problems(), [LevelProblem::NoEntrance(0)]);
+}
+
+#[test]
+fn duplicate_gems_are_rejected() {
+    let mut rows = [""; Levels::LEVEL_HEIGHT];
+    rows[Levels::LEVEL_HEIGHT - 2] = "e e";
+    rows[Levels::LEVEL_HEIGHT - 1] = "x x";
+
+    assert!(matches!(
+        level_text(&rows).parse::<Levels>(),
+        Err(ParseLevelError::DuplicateGem('e')),
+    ));
+}
+
+#[test]
+fn gems_must_be_on_solid_tiles() {
+    let mut rows = [""; Levels::LEVEL_HEIGHT];
+    rows[Levels::LEVEL_HEIGHT - 2] = "E";
+
+    assert!(matches!(
+        level_text(&rows).parse::<Levels>(),
+        Err(ParseLevelError::InvalidTileBelowGem),
+    ));
+}
+
+#[test]
+fn tile_kinds_round_trip() {
+    let text = level_text(&[
+        "", "", "", "", "", "", "", " c?soj", "-----", "^^^^^", "xxxxx",
+    ]);
+    let levels = text.parse::<Levels>().unwrap();
+
+    assert_eq!(levels[[0, 0]], Tile::Solid);
+    assert_eq!(levels[[0, 1]], Tile::Spike);
+    assert_eq!(levels[[0, 2]], Tile::OneWay);
+    assert_eq!(levels[[1, 3]], Tile::Checkpoint);
+    assert_eq!(levels[[2, 3]], Tile::Curse);
+    assert_eq!(levels[[3, 3]], Tile::Switch);
+    assert_eq!(levels[[4, 3]], Tile::Coin);
+    assert_eq!(levels[[5, 3]], Tile::DoubleJump);
+    assert_eq!(levels.to_string().parse::<Levels>().unwrap(), levels);
+}
+
+#[test]
+fn physics_overrides_round_trip() {
+    let text = format!(
+        "#version {}\n#level 1\n#gravity_scale 0.5\n#jump_scale 1.25\n{}",
+        Levels::FORMAT_VERSION,
+        level_text(&[]),
+    );
+
+    let levels = text.parse::<Levels>().unwrap();
+    let physics = levels.metadata[0].physics;
+
+    assert_eq!(physics.gravity_scale, Some(0.5));
+    assert_eq!(physics.friction_scale, None);
+    assert_eq!(physics.jump_scale, Some(1.25));
+    assert_eq!(levels.to_string(), text);
+}
+
+#[test]
+fn physics_scales_must_be_finite_and_positive() {
+    for scale in ["0", "-1", "NaN", "inf"] {
+        let text = format!(
+            "#version {}\n#level 1\n#gravity_scale {scale}\n{}",
+            Levels::FORMAT_VERSION,
+            level_text(&[]),
+        );
+
+        assert!(matches!(
+            text.parse::<Levels>(),
+            Err(ParseLevelError::InvalidHeaderLine(2)),
+        ));
+    }
+}
+
+#[test]
+fn pickup_radii_round_trip() {
+    let text = format!(
+        "#version {}\n#level 1\n#coin_radius 0.75\n#double_jump_radius 0\n{}",
+        Levels::FORMAT_VERSION,
+        level_text(&[]),
+    );
+
+    let levels = text.parse::<Levels>().unwrap();
+    let physics = levels.metadata[0].physics;
+
+    assert_eq!(physics.pickup_radii, [None, Some(0.75), Some(0.0)]);
+    assert_eq!(levels.to_string(), text);
+}
+
+#[test]
+fn negative_pickup_radii_are_rejected() {
+    let text = format!(
+        "#version {}\n#level 1\n#gem_radius -1\n{}",
+        Levels::FORMAT_VERSION,
+        level_text(&[]),
+    );
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::InvalidHeaderLine(2)),
+    ));
+}
+
+#[test]
+fn switch_regions_round_trip() {
+    let text = format!(
+        "#version {}\n#level 1\n#switch_region 2 1 5 3\n{}",
+        Levels::FORMAT_VERSION,
+        level_text(&[]),
+    );
+
+    let levels = text.parse::<Levels>().unwrap();
+
+    assert_eq!(levels.metadata[0].switch_region, Some([[2, 1], [5, 3]]));
+    assert_eq!(levels.to_string(), text);
+}
+
+#[test]
+fn switch_regions_outside_the_level_are_rejected() {
+    let text = format!(
+        "#version {}\n#level 1\n#switch_region 0 0 {} 0\n{}",
+        Levels::FORMAT_VERSION,
+        Levels::LEVEL_WIDTH - 1,
+        level_text(&[]),
+    );
+
+    assert!(matches!(
+        text.parse::<Levels>(),
+        Err(ParseLevelError::InvalidHeaderLine(2)),
+    ));
+}
+
+#[test]
+fn lenient_parsing_fixes_hand_editing_mistakes() {
+    let text = level_text(&["x", "", "xxx"]);
+    let mut lines = text.lines().map(str::to_owned).collect::<Vec<_>>();
+
+    lines[0].push_str("  ");
+    lines[2] = lines[2].trim_end_matches([' ', '|']).to_owned();
+
+    let messy = format!("{}\r\n\r\n", lines.join("\r\n"));
+
+    assert!(messy.parse::<Levels>().is_err());
+
+    let (levels, warnings) = Levels::parse_lenient(&messy).unwrap();
+
+    assert_eq!(levels, text.parse::<Levels>().unwrap());
+    assert_eq!(
+        warnings,
+        [
+            ParseWarning::TrailingBlankLines,
+            ParseWarning::TrailingWhitespace(0),
+            ParseWarning::MissingTerminator(2),
+        ]
+    );
+}
+
+#[test]
+fn lenient_parsing_still_rejects_real_errors() {
+    assert!(Levels::parse_lenient(&level_text(&["q"])).is_err());
+    assert_eq!(Levels::parse_lenient(ORIGINAL_LEVELS).unwrap().1, []);
+}
@@ -0,0 +1,31 @@
+use inverse::pickup::PickupTrigger;
+
+const FRAME: f32 = 1.0 / 60.0;
+
+#[test]
+fn standing_on_a_pickup_triggers_it_once() {
+    let mut trigger = PickupTrigger::default();
+
+    assert!(!trigger.update(false, FRAME));
+    assert!(trigger.update(true, FRAME));
+
+    for _ in 0..120 {
+        assert!(!trigger.update(true, FRAME));
+    }
+}
+
+#[test]
+fn pickups_trigger_again_after_leaving_and_cooling_down() {
+    let mut trigger = PickupTrigger::default();
+
+    assert!(trigger.update(true, FRAME));
+    assert!(!trigger.update(false, FRAME));
+
+    // Touching it again straight away is ignored until the player leaves again
+    assert!(!trigger.update(true, FRAME));
+    assert!(!trigger.update(true, PickupTrigger::COOLDOWN));
+    assert!(!trigger.is_cooling_down());
+
+    assert!(!trigger.update(false, FRAME));
+    assert!(trigger.update(true, FRAME));
+}
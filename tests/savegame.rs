@@ -0,0 +1,136 @@
+use inverse::level::{LevelChange, Levels};
+use inverse::player::{Player, SpawnPoint};
+use inverse::savegame::SaveGame;
+
+#[test]
+fn saves_round_trip() {
+    let mut save = SaveGame {
+        level_index: 2,
+        spawn_point: Some(SpawnPoint {
+            position: [0.25, 3.25],
+            air_kind: true,
+        }),
+        furthest_level: 3,
+        limited_gem_collected: true,
+        full_gem_collected: false,
+        deaths: 12,
+        coins: [4, 30].into(),
+        seen_version: Some("0.1.0".to_owned()),
+        ..Default::default()
+    };
+    save.record_time(1, 9.5, false);
+    save.record_split(0, 4.25, false);
+    save.debug_flags.noclip = true;
+    save.record_time(2, 3.0, true);
+
+    assert_eq!(save.to_string().parse::<SaveGame>().unwrap(), save);
+}
+
+#[test]
+fn only_the_best_time_is_kept() {
+    let mut save = SaveGame::default();
+
+    assert!(save.record_time(0, 10.0, false));
+    assert!(!save.record_time(0, 12.0, false));
+    assert!(save.record_time(0, 8.0, false));
+    assert_eq!(save.best_times[&0], 8.0);
+}
+
+#[test]
+fn debug_times_never_replace_real_ones() {
+    let mut save = SaveGame::default();
+
+    save.debug_flags.invincible = true;
+    assert!(save.record_time(0, 10.0, true));
+    assert!(save.debug_times.contains(&0));
+    assert!(!save.record_split(0, 10.0, true));
+
+    save.debug_flags.invincible = false;
+    assert!(save.record_time(0, 12.0, false));
+    assert!(!save.debug_times.contains(&0));
+
+    save.debug_flags.noclip = true;
+    assert!(!save.record_time(0, 1.0, true));
+    assert_eq!(save.best_times[&0], 12.0);
+}
+
+#[test]
+fn resuming_puts_the_player_back_in_their_level() {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+
+    let mut player = Player::new(false);
+    levels.next_level();
+    player.spawn_point.position = [0.25, 5.25];
+    player.deaths = 4;
+
+    let mut save = SaveGame::default();
+    assert!(save.record(&levels, &player));
+
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+    let mut player = Player::new(false);
+
+    save.resume(&mut levels, &mut player);
+
+    assert_eq!(levels.level_index, 1);
+    assert_eq!(player.position, [0.25, 5.25]);
+    assert_eq!(player.deaths, 4);
+}
+
+#[test]
+fn progress_follows_rearranged_levels() {
+    let level_tiles = (Levels::LEVEL_WIDTH - 1) * Levels::LEVEL_HEIGHT;
+
+    let mut save = SaveGame {
+        level_index: 1,
+        furthest_level: 2,
+        coins: [level_tiles + 7, 2 * level_tiles + 3].into(),
+        ..Default::default()
+    };
+    save.record_time(0, 5.0, false);
+    save.record_split(2, 30.0, false);
+    save.debug_flags.noclip = true;
+    save.record_time(2, 9.0, true);
+
+    // The last level moves to the front, so the others each move along one
+    save.remap_levels(LevelChange::Moved { from: 2, to: 0 });
+
+    assert_eq!(save.level_index, 2);
+    assert_eq!(save.furthest_level, 2);
+    assert_eq!(save.coins, [3, 2 * level_tiles + 7].into());
+    assert_eq!(save.best_times, [(0, 9.0), (1, 5.0)].into());
+    assert_eq!(save.debug_times, [0].into());
+    assert_eq!(save.best_splits, [(0, 30.0)].into());
+
+    // Duplicating a level before the furthest one unlocks its copy too
+    save.remap_levels(LevelChange::Duplicated(0));
+
+    assert_eq!(save.level_index, 3);
+    assert_eq!(save.furthest_level, 3);
+    assert_eq!(save.best_times, [(0, 9.0), (2, 5.0)].into());
+}
+
+#[test]
+fn collected_gems_unlock_their_editor() {
+    let mut save = SaveGame::default();
+    assert!(save.unlocked_editor().is_none());
+
+    save.limited_gem_collected = true;
+    assert!(
+        save.unlocked_editor()
+            .is_some_and(|editor| editor.is_limited())
+    );
+
+    save.full_gem_collected = true;
+    let save = save.to_string().parse::<SaveGame>().unwrap();
+    assert!(
+        save.unlocked_editor()
+            .is_some_and(|editor| editor.is_full())
+    );
+}
+
+#[test]
+fn unknown_lines_are_rejected() {
+    assert!("score = 100\n".parse::<SaveGame>().is_err());
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "serde")]
+
+use inverse::level::Levels;
+use inverse::tile::Tile;
+
+const ORIGINAL_LEVELS: &str = include_str!("../original_levels.txt");
+
+#[test]
+fn original_levels_round_trip_through_json() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+
+    assert_eq!(
+        Levels::from_json(&levels.to_json().unwrap()).unwrap(),
+        levels
+    );
+}
+
+#[test]
+fn original_levels_round_trip_through_ron() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+
+    assert_eq!(Levels::from_ron(&levels.to_ron().unwrap()).unwrap(), levels);
+}
+
+#[test]
+fn missing_tiles_are_rejected() {
+    let levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+    let mut json = serde_json::to_value(&levels).unwrap();
+
+    json["tiles"].as_array_mut().unwrap().pop();
+
+    assert!(Levels::from_json(&json.to_string()).is_err());
+}
+
+#[test]
+fn floating_gems_are_rejected() {
+    let mut levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+    let gem_index = levels.full_gem.unwrap();
+    levels.tiles[gem_index - 1] = Tile::Empty;
+
+    assert!(Levels::from_json(&levels.to_json().unwrap()).is_err());
+}
+
+#[test]
+fn switch_regions_outside_the_level_are_rejected() {
+    let mut levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+    levels.metadata[0].switch_region = Some([[0, 0], [Levels::LEVEL_WIDTH - 1, 0]]);
+
+    assert!(matches!(
+        Levels::from_json(&levels.to_json().unwrap()),
+        Err(error) if error.to_string().contains("level 1"),
+    ));
+}
+
+#[test]
+fn negative_pickup_radii_are_rejected() {
+    let mut levels = ORIGINAL_LEVELS.parse::<Levels>().unwrap();
+    levels.metadata[0].physics.pickup_radii[0] = Some(-1.0);
+
+    assert!(Levels::from_json(&levels.to_json().unwrap()).is_err());
+}
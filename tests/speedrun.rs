@@ -0,0 +1,88 @@
+use inverse::level::Levels;
+use inverse::player::Player;
+use inverse::savegame::SaveGame;
+use inverse::speedrun::{self, SpeedrunTimer};
+
+fn two_levels() -> Levels {
+    let mut levels = Levels::new();
+    levels.insert_level(1);
+    levels
+}
+
+#[test]
+fn timer_starts_on_the_first_input() {
+    let levels = two_levels();
+    let mut player = Player::new(false);
+    let mut timer = SpeedrunTimer::default();
+
+    timer.update(&levels, &player, false);
+    assert!(!timer.is_running());
+
+    player.inputs_down[1] = true;
+    timer.update(&levels, &player, false);
+    timer.update(&levels, &player, false);
+
+    assert!(timer.is_running());
+    assert_eq!(timer.time(), 1.0 / Player::UPDATES_PER_SECOND);
+}
+
+#[test]
+fn splits_are_compared_with_the_best_ones() {
+    let levels = two_levels();
+    let mut player = Player::new(false);
+    let mut savegame = SaveGame::default();
+    savegame.record_split(0, 1.0, false);
+
+    let mut timer = SpeedrunTimer::default();
+    player.inputs_down[1] = true;
+
+    for _ in 0..=Player::UPDATES_PER_SECOND as usize / 2 {
+        timer.update(&levels, &player, false);
+    }
+
+    assert_eq!(timer.delta(&levels, &savegame), Some(-0.5));
+
+    // Only going forwards out of the next level of the run counts
+    assert!(!timer.split(1, &mut savegame));
+    assert!(timer.split(0, &mut savegame));
+    assert_eq!(savegame.best_splits[&0], 0.5);
+    assert_eq!(timer.delta(&levels, &savegame), None);
+
+    assert!(timer.split(1, &mut savegame));
+    assert!(timer.is_finished(&levels));
+
+    let final_time = timer.time();
+    timer.update(&levels, &player, false);
+    assert_eq!(timer.time(), final_time);
+}
+
+#[test]
+fn runs_which_used_debug_flags_keep_no_splits() {
+    let levels = two_levels();
+    let mut player = Player::new(false);
+    let mut savegame = SaveGame::default();
+
+    let mut timer = SpeedrunTimer::default();
+    player.inputs_down[1] = true;
+
+    timer.update(&levels, &player, false);
+    timer.update(&levels, &player, true);
+    timer.update(&levels, &player, false);
+
+    assert!(!timer.split(0, &mut savegame));
+    assert!(savegame.best_splits.is_empty());
+
+    // A new run starts without them
+    let mut timer = SpeedrunTimer::default();
+
+    timer.update(&levels, &player, false);
+    timer.update(&levels, &player, false);
+
+    assert!(timer.split(0, &mut savegame));
+}
+
+#[test]
+fn times_are_formatted_as_minutes_and_seconds() {
+    assert_eq!(speedrun::format_time(65.25), "1:05.25");
+    assert_eq!(speedrun::format_time(-3.5), "0:03.50");
+}
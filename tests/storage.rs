@@ -0,0 +1,120 @@
+use std::{env, fs, path::PathBuf, process};
+
+use inverse::savegame::SaveGame;
+use inverse::storage::{Autosave, TrackedFile};
+
+/// A file in a fresh directory for one test, which is removed when dropped
+struct TestFile {
+    directory: PathBuf,
+    file: TrackedFile,
+}
+
+impl TestFile {
+    fn new(name: &str) -> Self {
+        let directory = env::temp_dir().join(format!("inverse-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+
+        Self {
+            file: TrackedFile {
+                path: directory.join("levels.txt"),
+                last_modified: None,
+            },
+            directory,
+        }
+    }
+}
+
+impl Drop for TestFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+#[test]
+fn writes_keep_rotating_backups() {
+    let mut test = TestFile::new("rotating-backups");
+    let file = &mut test.file;
+
+    for version in 0..5 {
+        file.write(version.to_string()).unwrap();
+    }
+
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), "4");
+
+    for number in 1..=TrackedFile::BACKUPS {
+        assert_eq!(
+            fs::read_to_string(file.backup_path(number)).unwrap(),
+            (4 - number).to_string()
+        );
+    }
+
+    assert!(!file.backup_path(TrackedFile::BACKUPS + 1).exists());
+    assert_eq!(fs::read_dir(&test.directory).unwrap().count(), 4);
+}
+
+#[test]
+fn restoring_a_backup_can_be_undone() {
+    let mut test = TestFile::new("restore-backup");
+    let file = &mut test.file;
+
+    file.write("old").unwrap();
+    file.write("new").unwrap();
+    file.restore_backup(1).unwrap();
+
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), "old");
+    assert_eq!(fs::read_to_string(file.backup_path(1)).unwrap(), "new");
+}
+
+#[test]
+fn unloadable_saves_are_set_aside_instead_of_overwritten() {
+    let mut test = TestFile::new("corrupt-save");
+    let file = &mut test.file;
+
+    fs::write(&file.path, "level = lots").unwrap();
+    assert!(SaveGame::load(file).is_err());
+
+    let corrupt_path = file.set_aside_corrupt().unwrap();
+    SaveGame::default().save(file).unwrap();
+
+    assert_eq!(fs::read_to_string(corrupt_path).unwrap(), "level = lots");
+    assert_eq!(SaveGame::load(file).unwrap(), SaveGame::default());
+}
+
+#[test]
+fn autosaves_wait_for_the_interval_after_the_first_change() {
+    let mut test = TestFile::new("autosave");
+    let mut autosave = Autosave::default();
+
+    assert!(!autosave.update(Autosave::INTERVAL));
+
+    autosave.mark_dirty();
+    assert!(!autosave.update(Autosave::INTERVAL / 2.0));
+
+    // Later changes don't push the save back
+    autosave.mark_dirty();
+    assert!(autosave.update(Autosave::INTERVAL / 2.0));
+
+    autosave.save(&mut test.file, "levels").unwrap();
+
+    assert!(!autosave.is_dirty());
+    assert_eq!(fs::read_to_string(&test.file.path).unwrap(), "levels");
+}
+
+#[test]
+fn failed_autosaves_stay_dirty() {
+    let mut autosave = Autosave::default();
+    let mut file = TrackedFile {
+        path: env::temp_dir()
+            .join(format!("inverse-missing-{}", process::id()))
+            .join("levels.txt"),
+        last_modified: None,
+    };
+
+    autosave.mark_dirty();
+    assert!(autosave.update(Autosave::INTERVAL));
+    assert!(autosave.save(&mut file, "levels").is_err());
+
+    assert!(autosave.is_dirty());
+    assert!(!autosave.update(0.0));
+}